@@ -0,0 +1,37 @@
+use crate::attack_maps::ray_attacks::{ray, RayDirection};
+use crate::attack_maps::sliders::SliderAttacks;
+use crate::types::bitboard::BitBoard;
+use crate::types::square::Square;
+
+pub struct HyperbolaQuintessence;
+
+fn line_attacks(square: Square, occupancy: BitBoard, line: BitBoard) -> BitBoard {
+    // o ^ (o - 2s) finds the attacks towards higher squares, the same trick on the reversed
+    // board finds them towards lower squares. Full bit reversal makes this work on ranks too.
+    let slider = square.as_bitboard();
+    let occupied = occupancy & line;
+
+    let forward = occupied.wrapping_sub(slider.wrapping_mul(2));
+    let reverse = occupied
+        .reverse_bits()
+        .wrapping_sub(slider.reverse_bits().wrapping_mul(2))
+        .reverse_bits();
+
+    (forward ^ reverse) & line
+}
+
+impl SliderAttacks for HyperbolaQuintessence {
+    fn rook_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        let file = ray(square, RayDirection::NORTH) | ray(square, RayDirection::SOUTH);
+        let rank = ray(square, RayDirection::EAST) | ray(square, RayDirection::WEST);
+
+        line_attacks(square, occupancy, file) | line_attacks(square, occupancy, rank)
+    }
+
+    fn bishop_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        let diagonal = ray(square, RayDirection::NOEAST) | ray(square, RayDirection::SOWEST);
+        let anti_diagonal = ray(square, RayDirection::NOWEST) | ray(square, RayDirection::SOEAST);
+
+        line_attacks(square, occupancy, diagonal) | line_attacks(square, occupancy, anti_diagonal)
+    }
+}
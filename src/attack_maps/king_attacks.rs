@@ -1,45 +1,45 @@
 use crate::board::{board_flat, BoardFlat};
 use crate::constants::{NOT_A_FILE, NOT_H_FILE, ZERO};
 use crate::types::bitboard::BitBoard;
+use crate::types::square::{Square, SQUARES};
 
 const fn generate_king_attacks() -> BoardFlat<BitBoard> {
     let mut result = board_flat(0);
     let mut i = 0;
 
-    loop {
-        if i == 63 {
-            return result;
-        } else {
-            result[i as usize] = calculate_king_attack_set(i);
-        }
-
+    while i < SQUARES.len() {
+        let square = SQUARES[i];
+        result[square as usize] = calculate_king_attack_set(square);
         i += 1;
     }
+
+    result
 }
 
-const fn calculate_king_attack_set(origin: BitBoard) -> BitBoard {
+pub const fn calculate_king_attack_set(square: Square) -> BitBoard {
+    let origin = square.as_bitboard();
     let mut result = ZERO;
 
-    result = result | (origin << 1 & NOT_H_FILE);
+    result = result | (origin << 1 & NOT_A_FILE);
     result = result | (origin << 7 & NOT_H_FILE);
     result = result | (origin << 8);
-    result = result | (origin << 9);
-    result = result | (origin >> 1 & NOT_A_FILE);
-    result = result | (origin >> 7);
+    result = result | (origin << 9 & NOT_A_FILE);
+    result = result | (origin >> 1 & NOT_H_FILE);
+    result = result | (origin >> 7 & NOT_A_FILE);
     result = result | (origin >> 8);
-    result = result | (origin >> 9 & NOT_A_FILE);
+    result = result | (origin >> 9 & NOT_H_FILE);
 
     result
 }
 
-pub const KING_ATTACKS: BoardFlat<BitBoard> = generate_king_attacks();
+pub static KING_ATTACKS: BoardFlat<BitBoard> = generate_king_attacks();
 
 #[cfg(test)]
 mod test {
     use crate::types::bitboard::{bitboard_from_str, bitboard_to_str};
     use crate::types::square::Square;
 
-    use super::calculate_king_attack_set;
+    use super::{calculate_king_attack_set, KING_ATTACKS};
 
     #[test]
     fn test_calculate_knigt_attack_set() {
@@ -55,9 +55,31 @@ mod test {
         )
         .unwrap();
 
-        let to_test = calculate_king_attack_set(Square::C4.as_bitboard());
+        let to_test = calculate_king_attack_set(Square::C4);
         println!("{}", bitboard_to_str(Square::C4.as_bitboard()));
         println!("{}", Square::C4.as_bitboard());
         assert_eq!(attack_on_c4, to_test);
     }
+
+    #[test]
+    fn test_king_attacks_on_edges() {
+        use crate::types::square::Square::*;
+
+        assert_eq!(
+            KING_ATTACKS[A1 as usize],
+            B1.as_bitboard() | A2.as_bitboard() | B2.as_bitboard()
+        );
+        assert_eq!(
+            KING_ATTACKS[H8 as usize],
+            G8.as_bitboard() | G7.as_bitboard() | H7.as_bitboard()
+        );
+        assert_eq!(
+            KING_ATTACKS[H4 as usize],
+            H5.as_bitboard()
+                | G5.as_bitboard()
+                | G4.as_bitboard()
+                | G3.as_bitboard()
+                | H3.as_bitboard()
+        );
+    }
 }
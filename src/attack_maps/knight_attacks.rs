@@ -1,23 +1,23 @@
 use crate::board::{board_flat, BoardFlat};
 use crate::constants::{NOT_AB_FILE, NOT_A_FILE, NOT_GH_FILE, NOT_H_FILE, ZERO};
 use crate::types::bitboard::BitBoard;
+use crate::types::square::{Square, SQUARES};
 
 const fn generate_knight_attacks() -> BoardFlat<BitBoard> {
     let mut result = board_flat(0);
     let mut i = 0;
 
-    loop {
-        if i == 63 {
-            return result;
-        } else {
-            result[i as usize] = calculate_knight_attack_set(i);
-        }
-
+    while i < SQUARES.len() {
+        let square = SQUARES[i];
+        result[square as usize] = calculate_knight_attack_set(square);
         i += 1;
     }
+
+    result
 }
 
-const fn calculate_knight_attack_set(origin: BitBoard) -> BitBoard {
+pub const fn calculate_knight_attack_set(square: Square) -> BitBoard {
+    let origin = square.as_bitboard();
     let mut result = ZERO;
 
     result = result | (origin << 17 & NOT_A_FILE);
@@ -32,14 +32,14 @@ const fn calculate_knight_attack_set(origin: BitBoard) -> BitBoard {
     result
 }
 
-pub const KNIGHT_ATTACKS: BoardFlat<BitBoard> = generate_knight_attacks();
+pub static KNIGHT_ATTACKS: BoardFlat<BitBoard> = generate_knight_attacks();
 
 #[cfg(test)]
 mod test {
     use crate::types::bitboard::bitboard_from_str;
     use crate::types::square::Square;
 
-    use super::calculate_knight_attack_set;
+    use super::{calculate_knight_attack_set, KNIGHT_ATTACKS};
 
     #[test]
     fn test_calculate_knigt_attack_set() {
@@ -55,7 +55,21 @@ mod test {
         )
         .unwrap();
 
-        let to_test = calculate_knight_attack_set(Square::D4.as_bitboard());
+        let to_test = calculate_knight_attack_set(Square::D4);
         assert_eq!(attack_on_d4, to_test);
     }
+
+    #[test]
+    fn test_knight_attacks_on_edges() {
+        use crate::types::square::Square::*;
+
+        assert_eq!(
+            KNIGHT_ATTACKS[H8 as usize],
+            F7.as_bitboard() | G6.as_bitboard()
+        );
+        assert_eq!(
+            KNIGHT_ATTACKS[A1 as usize],
+            B3.as_bitboard() | C2.as_bitboard()
+        );
+    }
 }
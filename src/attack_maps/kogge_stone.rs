@@ -0,0 +1,53 @@
+use crate::attack_maps::sliders::SliderAttacks;
+use crate::types::bitboard::{
+    east_one, north_east_one, north_one, north_west_one, south_east_one, south_one, south_west_one,
+    west_one, BitBoard,
+};
+use crate::types::square::Square;
+
+pub struct KoggeStone;
+
+type Shift = fn(BitBoard) -> BitBoard;
+
+fn occluded_fill(mut generator: BitBoard, mut propagator: BitBoard, shift: Shift) -> BitBoard {
+    // Parallel prefix fill in three steps of one, two and four squares. The shift helpers
+    // already drop squares wrapping around the board edge, so no file masks are needed here.
+    generator |= propagator & shift(generator);
+    propagator &= shift(propagator);
+    generator |= propagator & shift(shift(generator));
+    propagator &= shift(shift(propagator));
+    generator |= propagator & shift(shift(shift(shift(generator))));
+
+    generator
+}
+
+fn sliding_attacks(square: Square, occupancy: BitBoard, shifts: [Shift; 4]) -> BitBoard {
+    let empty = !occupancy;
+
+    shifts.iter().fold(0, |acc, shift| {
+        acc | shift(occluded_fill(square.as_bitboard(), empty, *shift))
+    })
+}
+
+impl SliderAttacks for KoggeStone {
+    fn rook_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        sliding_attacks(
+            square,
+            occupancy,
+            [north_one, south_one, east_one, west_one],
+        )
+    }
+
+    fn bishop_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        sliding_attacks(
+            square,
+            occupancy,
+            [
+                north_east_one,
+                north_west_one,
+                south_east_one,
+                south_west_one,
+            ],
+        )
+    }
+}
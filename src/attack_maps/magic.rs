@@ -0,0 +1,359 @@
+use std::sync::OnceLock;
+
+use strum::IntoEnumIterator;
+
+use crate::attack_maps::ray_attacks::{
+    self, ray, RayDirection, BISHOP_DIRECTIONS, ROOK_DIRECTIONS,
+};
+use crate::attack_maps::sliders::SliderAttacks;
+use crate::board::BoardFlat;
+use crate::constants::{SQUARE_COUNT, ZERO};
+use crate::types::bitboard::BitBoard;
+use crate::types::square::Square;
+
+// Seed that reproduces ROOK_MAGICS and BISHOP_MAGICS with find_magics.
+pub const MAGIC_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Slider {
+    ROOK,
+    BISHOP,
+}
+
+impl Slider {
+    pub(crate) fn reference_attacks(self, square: Square, occupancy: BitBoard) -> BitBoard {
+        match self {
+            Slider::ROOK => ray_attacks::rook_attacks(square, occupancy),
+            Slider::BISHOP => ray_attacks::bishop_attacks(square, occupancy),
+        }
+    }
+}
+
+fn ray_edge(direction: RayDirection) -> BitBoard {
+    // The last square of a ray never changes the attack set, so it is left out of the mask.
+    // Those are the squares from which the ray has nowhere left to go.
+    Square::iter()
+        .filter(|square| ray(*square, direction) == ZERO)
+        .fold(ZERO, |acc, square| acc | square.as_bitboard())
+}
+
+pub fn relevant_occupancy_mask(slider: Slider, square: Square) -> BitBoard {
+    let directions = match slider {
+        Slider::ROOK => ROOK_DIRECTIONS,
+        Slider::BISHOP => BISHOP_DIRECTIONS,
+    };
+
+    directions.iter().fold(ZERO, |acc, direction| {
+        acc | (ray(square, *direction) & !ray_edge(*direction))
+    })
+}
+
+pub(crate) fn occupancy_subsets(mask: BitBoard) -> Vec<BitBoard> {
+    // Carry-Rippler enumeration of every subset of the mask, starting with the empty set.
+    let mut result = Vec::with_capacity(1 << mask.count_ones());
+    let mut subset = ZERO;
+
+    loop {
+        result.push(subset);
+        subset = subset.wrapping_sub(mask) & mask;
+
+        if subset == ZERO {
+            return result;
+        }
+    }
+}
+
+pub struct MagicRng(u64);
+
+impl MagicRng {
+    pub fn new(seed: u64) -> MagicRng {
+        // Xorshift64* has a single fixed point at zero, which the seed must avoid.
+        MagicRng(if seed == 0 { MAGIC_SEED } else { seed })
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn sparse_u64(&mut self) -> u64 {
+        // Magics with few set bits are found much faster.
+        self.next_u64() & self.next_u64() & self.next_u64()
+    }
+}
+
+pub fn find_magic(slider: Slider, square: Square, rng: &mut MagicRng) -> u64 {
+    let mask = relevant_occupancy_mask(slider, square);
+    let bits = mask.count_ones();
+    let occupancies = occupancy_subsets(mask);
+    let attacks: Vec<BitBoard> = occupancies
+        .iter()
+        .map(|occupancy| slider.reference_attacks(square, *occupancy))
+        .collect();
+
+    // Slider attacks are never empty, so an empty entry marks an unused slot.
+    let mut used = vec![ZERO; 1 << bits];
+
+    loop {
+        let magic = rng.sparse_u64();
+
+        if (mask.wrapping_mul(magic) >> 56).count_ones() < 6 {
+            continue;
+        }
+
+        used.iter_mut().for_each(|entry| *entry = ZERO);
+
+        let collision = occupancies.iter().zip(&attacks).any(|(occupancy, attack)| {
+            let index = (occupancy.wrapping_mul(magic) >> (64 - bits)) as usize;
+
+            if used[index] == ZERO {
+                used[index] = *attack;
+                false
+            } else {
+                used[index] != *attack
+            }
+        });
+
+        if !collision {
+            return magic;
+        }
+    }
+}
+
+pub fn find_magics(slider: Slider, seed: u64) -> BoardFlat<u64> {
+    let mut rng = MagicRng::new(seed);
+    let mut result = [0; SQUARE_COUNT as usize];
+
+    for (i, magic) in result.iter_mut().enumerate() {
+        *magic = find_magic(slider, Square::try_from(i as u64).unwrap(), &mut rng);
+    }
+
+    result
+}
+
+#[rustfmt::skip]
+pub const ROOK_MAGICS: BoardFlat<u64> = [
+    0x008000908064C000, 0x0040200040001000, 0x0180100080A0010A, 0x8880041000800800,
+    0x1200100201200804, 0x0200020004011008, 0x2180010000800600, 0x0200005088210204,
+    0x0400800040008021, 0x0400400020005000, 0x8240801000200080, 0x8611001004200900,
+    0x008180800C001800, 0x0100800200800400, 0x0A02000102000408, 0x8020802300104280,
+    0x0080004000402000, 0xE010104000402000, 0x0800808010002000, 0xA280210008100100,
+    0x0001818014000800, 0xA002010100080400, 0x0080240001020870, 0x0001020004048845,
+    0x0081826280004004, 0x2020810900284000, 0x0200100080802000, 0x0200080080100080,
+    0x8083080100100500, 0x4406000901000400, 0x0005020080800100, 0x0090204200008114,
+    0x0010400094800420, 0x0900804000802002, 0x0201001841002000, 0x4100080080801000,
+    0x4540040080800800, 0x0002001004040020, 0x0281195814001002, 0x1240800040800100,
+    0x0880042000524004, 0x02C080410206002C, 0x0801200241050010, 0x8400080010008080,
+    0x0008000500090010, 0x0082009084020008, 0x4012000108020004, 0x9000104D08860004,
+    0x2004204114800100, 0x0148802112400300, 0x0202842000100880, 0x001B080080900080,
+    0x001A002008100600, 0x0004008004020080, 0x5181000600040300, 0x0000044401128A00,
+    0x8044110480002441, 0x2008110084402202, 0x90806005090010C1, 0x000420310A004A42,
+    0x0023001004020801, 0x0882001008040102, 0x000230088118020C, 0x0000019025040042,
+];
+
+#[rustfmt::skip]
+pub const BISHOP_MAGICS: BoardFlat<u64> = [
+    0x0020428400408200, 0x2008010104210004, 0x02D0009200480190, 0x0018158B00010100,
+    0x02C4042132048008, 0x020082202000C221, 0x4000421050080009, 0x0210140202022020,
+    0x00C0101410042248, 0x0405204800D48080, 0x3800C89200420002, 0x180844124A020440,
+    0x04403410A8002221, 0x4040209004200400, 0x084004020202A204, 0x3010002104022000,
+    0x00200240A9110900, 0x2302800404080210, 0x0204188800240010, 0x8048000C01401200,
+    0x120C001A11040900, 0x0000401200500440, 0x00004040840420A0, 0x0020930822880804,
+    0x4044401090900161, 0x0034100015210804, 0x8004100009010120, 0x48C8080000820500,
+    0x0080848004002000, 0x0801004012005044, 0x000080902C040400, 0x0004009005004100,
+    0x0B103010048A0200, 0x8004100203181A00, 0x0800140200100080, 0x8401010800910040,
+    0x0840010011290040, 0x40100214202E1000, 0x0842040040010840, 0x0028010040010860,
+    0x00080202A2051000, 0x4200841008084204, 0x0021120110000D02, 0x48C1004208000084,
+    0x0010088100414400, 0x0021101000420580, 0x0010040558401410, 0x200C0C82A1050205,
+    0x0011108820088000, 0x0001011910120402, 0x1580008608091248, 0x8010018020880C02,
+    0x20A1101032088480, 0x0080100408082800, 0x28100401140401C0, 0x8002102200930012,
+    0x4001040082080200, 0x082200A498081808, 0x000508610080D003, 0x0052020044842402,
+    0x4800A00140C84840, 0x5000000848080820, 0x0101086004240040, 0x0028280808005014,
+];
+
+#[derive(Debug, Copy, Clone, Default)]
+struct MagicEntry {
+    mask: BitBoard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl MagicEntry {
+    fn index(&self, occupancy: BitBoard) -> usize {
+        self.offset + ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+struct MagicTables {
+    rook: BoardFlat<MagicEntry>,
+    bishop: BoardFlat<MagicEntry>,
+    attacks: Vec<BitBoard>,
+}
+
+fn fill_magic_entries(
+    slider: Slider,
+    magics: &BoardFlat<u64>,
+    attacks: &mut Vec<BitBoard>,
+) -> BoardFlat<MagicEntry> {
+    // All squares share one attack table, each square owns a slice starting at its offset.
+    let mut entries = [MagicEntry::default(); SQUARE_COUNT as usize];
+
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let square = Square::try_from(i as u64).unwrap();
+        let mask = relevant_occupancy_mask(slider, square);
+
+        *entry = MagicEntry {
+            mask,
+            magic: magics[i],
+            shift: 64 - mask.count_ones(),
+            offset: attacks.len(),
+        };
+
+        attacks.resize(attacks.len() + (1 << mask.count_ones()), ZERO);
+
+        for occupancy in occupancy_subsets(mask) {
+            attacks[entry.index(occupancy)] = slider.reference_attacks(square, occupancy);
+        }
+    }
+
+    entries
+}
+
+fn magic_tables() -> &'static MagicTables {
+    static TABLES: OnceLock<MagicTables> = OnceLock::new();
+
+    TABLES.get_or_init(|| {
+        let mut attacks = Vec::new();
+        let rook = fill_magic_entries(Slider::ROOK, &ROOK_MAGICS, &mut attacks);
+        let bishop = fill_magic_entries(Slider::BISHOP, &BISHOP_MAGICS, &mut attacks);
+
+        MagicTables {
+            rook,
+            bishop,
+            attacks,
+        }
+    })
+}
+
+pub fn rook_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+    let tables = magic_tables();
+    tables.attacks[tables.rook[square as usize].index(occupancy)]
+}
+
+pub fn bishop_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+    let tables = magic_tables();
+    tables.attacks[tables.bishop[square as usize].index(occupancy)]
+}
+
+pub fn queen_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+    rook_attacks(square, occupancy) | bishop_attacks(square, occupancy)
+}
+
+pub struct Magic;
+
+impl SliderAttacks for Magic {
+    fn rook_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        rook_attacks(square, occupancy)
+    }
+
+    fn bishop_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        bishop_attacks(square, occupancy)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::attack_maps::ray_walk::{bishop_ray_walk, rook_ray_walk};
+    use crate::types::bitboard::bitboard_from_str;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_relevant_occupancy_mask() {
+        let rook_d4 = bitboard_from_str(
+            "........
+             ...x....
+             ...x....
+             ...x....
+             .xx.xxx.
+             ...x....
+             ...x....
+             ........",
+        )
+        .unwrap();
+        assert_eq!(relevant_occupancy_mask(Slider::ROOK, Square::D4), rook_d4);
+
+        let bishop_a1 = bitboard_from_str(
+            "........
+             ......x.
+             .....x..
+             ....x...
+             ...x....
+             ..x.....
+             .x......
+             ........",
+        )
+        .unwrap();
+        assert_eq!(
+            relevant_occupancy_mask(Slider::BISHOP, Square::A1),
+            bishop_a1
+        );
+
+        assert_eq!(
+            relevant_occupancy_mask(Slider::ROOK, Square::A1).count_ones(),
+            12
+        );
+    }
+
+    #[test]
+    fn test_occupancy_subsets() {
+        let mask = Square::A1.as_bitboard() | Square::C3.as_bitboard() | Square::H8.as_bitboard();
+        let subsets = occupancy_subsets(mask);
+
+        assert_eq!(subsets.len(), 8);
+        assert!(subsets.iter().all(|subset| subset & !mask == ZERO));
+    }
+
+    #[test]
+    fn test_magics_are_reproducible_from_seed() {
+        assert_eq!(find_magics(Slider::BISHOP, MAGIC_SEED), BISHOP_MAGICS);
+        assert_eq!(find_magics(Slider::ROOK, MAGIC_SEED), ROOK_MAGICS);
+    }
+
+    #[test]
+    fn test_magic_attacks_match_ray_walk_for_every_subset() {
+        for square in Square::iter() {
+            for occupancy in occupancy_subsets(relevant_occupancy_mask(Slider::ROOK, square)) {
+                assert_eq!(
+                    rook_attacks(square, occupancy),
+                    rook_ray_walk(square, occupancy)
+                );
+            }
+
+            for occupancy in occupancy_subsets(relevant_occupancy_mask(Slider::BISHOP, square)) {
+                assert_eq!(
+                    bishop_attacks(square, occupancy),
+                    bishop_ray_walk(square, occupancy)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_magic_attacks_ignore_irrelevant_occupancy() {
+        // Pieces on edge squares and outside the rays must not change the lookup.
+        let full = !ZERO;
+
+        for square in Square::iter() {
+            assert_eq!(rook_attacks(square, full), rook_ray_walk(square, full));
+            assert_eq!(bishop_attacks(square, full), bishop_ray_walk(square, full));
+            assert_eq!(
+                queen_attacks(square, full),
+                rook_ray_walk(square, full) | bishop_ray_walk(square, full)
+            );
+        }
+    }
+}
@@ -1,4 +1,12 @@
+pub mod hyperbola;
 pub mod king_attacks;
 pub mod knight_attacks;
+pub mod kogge_stone;
+pub mod magic;
 pub mod pawn_attack;
+pub mod pext;
 pub mod ray_attacks;
+pub mod ray_walk;
+#[cfg(test)]
+mod reference;
+pub mod sliders;
@@ -1,7 +1,8 @@
-use crate::constants::{NOT_A_FILE, NOT_H_FILE};
-use crate::piece::Color;
+use crate::board::{board_flat, BoardFlat};
+use crate::constants::{ONE, SQUARE_COUNT};
+use crate::piece::{Color, COLOR_COUNT};
 use crate::types::bitboard::{
-    north_east_one, north_west_one, south_east_one, south_west_one, BitBoard,
+    north_east_one, north_one, north_west_one, south_east_one, south_one, south_west_one, BitBoard,
 };
 
 fn pawn_west_attacks(color: Color, pawn_bitboard: BitBoard) -> BitBoard {
@@ -30,12 +31,49 @@ pub fn pawn_single_attacks(color: Color, pawn_bitboard: BitBoard) -> BitBoard {
     pawn_west_attacks(color, pawn_bitboard) ^ pawn_east_attacks(color, pawn_bitboard)
 }
 
+const fn generate_pawn_attacks() -> [BoardFlat<BitBoard>; COLOR_COUNT] {
+    let mut result = [board_flat(0); COLOR_COUNT];
+    let mut i = 0;
+
+    while i < SQUARE_COUNT {
+        let origin = ONE << i;
+
+        result[Color::WHITE.index()][i as usize] = north_west_one(origin) | north_east_one(origin);
+        result[Color::BLACK.index()][i as usize] = south_west_one(origin) | south_east_one(origin);
+        i += 1;
+    }
+
+    result
+}
+
+const fn generate_pawn_pushes() -> [BoardFlat<BitBoard>; COLOR_COUNT] {
+    // Single push targets only, the double push is derived from these by the caller.
+    let mut result = [board_flat(0); COLOR_COUNT];
+    let mut i = 0;
+
+    while i < SQUARE_COUNT {
+        let origin = ONE << i;
+
+        result[Color::WHITE.index()][i as usize] = north_one(origin);
+        result[Color::BLACK.index()][i as usize] = south_one(origin);
+        i += 1;
+    }
+
+    result
+}
+
+pub static PAWN_ATTACKS: [BoardFlat<BitBoard>; COLOR_COUNT] = generate_pawn_attacks();
+
+pub static PAWN_PUSHES: [BoardFlat<BitBoard>; COLOR_COUNT] = generate_pawn_pushes();
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::attack_maps::pawn_attack::pawn_any_attacks;
     use crate::piece::Color::*;
     use crate::types::bitboard::{bitboard_from_str, bitboard_to_str};
+    use crate::types::square::Square;
+    use strum::IntoEnumIterator;
 
     #[test]
     fn test_pawn_west_attacks() {
@@ -195,4 +233,33 @@ mod test {
         let attacks = pawn_any_attacks(WHITE, start_pos);
         assert_eq!(west_attacks_white, attacks);
     }
+
+    #[test]
+    fn test_pawn_attack_table_matches_set_wise_attacks() {
+        for square in Square::iter() {
+            for color in [WHITE, BLACK] {
+                assert_eq!(
+                    PAWN_ATTACKS[color.index()][square as usize],
+                    pawn_any_attacks(color, square.as_bitboard()),
+                    "{:?} pawn on {}",
+                    color,
+                    square
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pawn_push_table() {
+        assert_eq!(
+            PAWN_PUSHES[WHITE.index()][Square::E2 as usize],
+            Square::E3.as_bitboard()
+        );
+        assert_eq!(
+            PAWN_PUSHES[BLACK.index()][Square::A7 as usize],
+            Square::A6.as_bitboard()
+        );
+        assert_eq!(PAWN_PUSHES[WHITE.index()][Square::H8 as usize], 0);
+        assert_eq!(PAWN_PUSHES[BLACK.index()][Square::B1 as usize], 0);
+    }
 }
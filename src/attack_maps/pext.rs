@@ -0,0 +1,164 @@
+use std::sync::OnceLock;
+
+use crate::attack_maps::magic::{occupancy_subsets, relevant_occupancy_mask, Slider};
+use crate::attack_maps::sliders::SliderAttacks;
+use crate::board::BoardFlat;
+use crate::constants::{SQUARE_COUNT, ZERO};
+use crate::types::bitboard::BitBoard;
+use crate::types::square::Square;
+
+pub struct Pext;
+
+pub type PextFn = fn(u64, u64) -> u64;
+
+pub fn select_pext() -> PextFn {
+    // BMI2 is detected once, when the tables are built, a build with -C target-feature=+bmi2
+    // resolves the check statically.
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("bmi2") {
+        return bmi2_pext;
+    }
+
+    software_pext
+}
+
+#[cfg(target_arch = "x86_64")]
+fn bmi2_pext(value: u64, mask: u64) -> u64 {
+    // SAFETY: select_pext only hands this out after checking that the CPU supports BMI2.
+    unsafe { hardware_pext(value, mask) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2")]
+unsafe fn hardware_pext(value: u64, mask: u64) -> u64 {
+    std::arch::x86_64::_pext_u64(value, mask)
+}
+
+pub fn software_pext(value: u64, mut mask: u64) -> u64 {
+    // Gathers the bits of value selected by mask into the low bits of the result, in order.
+    let mut result = 0;
+    let mut bit = 1;
+
+    while mask != 0 {
+        if value & mask & mask.wrapping_neg() != 0 {
+            result |= bit;
+        }
+
+        mask &= mask - 1;
+        bit <<= 1;
+    }
+
+    result
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+struct PextEntry {
+    mask: BitBoard,
+    offset: usize,
+}
+
+impl PextEntry {
+    fn index(&self, pext: PextFn, occupancy: BitBoard) -> usize {
+        self.offset + pext(occupancy, self.mask) as usize
+    }
+}
+
+struct PextTables {
+    rook: BoardFlat<PextEntry>,
+    bishop: BoardFlat<PextEntry>,
+    attacks: Vec<BitBoard>,
+    pext: PextFn,
+}
+
+fn fill_pext_entries(
+    slider: Slider,
+    pext: PextFn,
+    attacks: &mut Vec<BitBoard>,
+) -> BoardFlat<PextEntry> {
+    // Same layout as the magic tables, but pext gives a perfect index so no magics are needed.
+    let mut entries = [PextEntry::default(); SQUARE_COUNT as usize];
+
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let square = Square::try_from(i as u64).unwrap();
+        let mask = relevant_occupancy_mask(slider, square);
+
+        *entry = PextEntry {
+            mask,
+            offset: attacks.len(),
+        };
+
+        attacks.resize(attacks.len() + (1 << mask.count_ones()), ZERO);
+
+        for occupancy in occupancy_subsets(mask) {
+            attacks[entry.index(pext, occupancy)] = slider.reference_attacks(square, occupancy);
+        }
+    }
+
+    entries
+}
+
+fn pext_tables() -> &'static PextTables {
+    static TABLES: OnceLock<PextTables> = OnceLock::new();
+
+    TABLES.get_or_init(|| {
+        let pext = select_pext();
+        let mut attacks = Vec::new();
+        let rook = fill_pext_entries(Slider::ROOK, pext, &mut attacks);
+        let bishop = fill_pext_entries(Slider::BISHOP, pext, &mut attacks);
+
+        PextTables {
+            rook,
+            bishop,
+            attacks,
+            pext,
+        }
+    })
+}
+
+impl SliderAttacks for Pext {
+    fn rook_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        let tables = pext_tables();
+        tables.attacks[tables.rook[square as usize].index(tables.pext, occupancy)]
+    }
+
+    fn bishop_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        let tables = pext_tables();
+        tables.attacks[tables.bishop[square as usize].index(tables.pext, occupancy)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_software_pext() {
+        assert_eq!(software_pext(0, 0), 0);
+        assert_eq!(software_pext(!0, 0xF0), 0xF);
+        assert_eq!(software_pext(0b1010_1010, 0b1111_0000), 0b1010);
+        assert_eq!(
+            software_pext(0x8000_0000_0000_0001, 0x8000_0000_0000_0001),
+            0b11
+        );
+        assert_eq!(
+            software_pext(0x0101_0101_0101_0101, 0x0303_0303_0303_0303),
+            0x5555
+        );
+    }
+
+    #[test]
+    fn test_pext_matches_software_pext() {
+        let pext = select_pext();
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+
+        for _ in 0..10_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let value = state;
+            let mask = state.rotate_left(29) & state.rotate_left(41);
+
+            assert_eq!(pext(value, mask), software_pext(value, mask));
+        }
+    }
+}
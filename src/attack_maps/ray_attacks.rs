@@ -1,21 +1,19 @@
 use crate::{
-    board::{board_flat, board_flat_non_copy, BoardFlat},
+    attack_maps::sliders::SliderAttacks,
+    board::{board_flat, BoardFlat},
     constants::{
-        A1_H8_DIAG, A_FILE, EIGHT_RANK, FILE_COUNT, H1_A8_DIAG, H_FILE, ONE_RANK, RANK_COUNT,
-        SQUARES_IN_RANK,
+        A1_H8_DIAG, A_FILE, EIGHT_RANK, FILE_COUNT, FULL_BOARD, H1_A8_DIAG, H_FILE, ONE_RANK,
+        RANK_COUNT, SQUARES_IN_RANK,
     },
     types::bitboard::{
-        bitboard_to_str, east_one, north_east_one, north_one, north_west_one, south_east_one,
-        south_one, south_west_one, west_one, BitBoard,
+        east_one, north_east_one, north_one, north_west_one, south_east_one, south_one,
+        south_west_one, west_one, BitBoard,
     },
-    types::square::Square,
+    types::square::{Square, SQUARES},
 };
 
-use std::collections::HashMap;
-use strum::IntoEnumIterator;
-
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum RayDirection {
+pub enum RayDirection {
     NORTH,
     NOEAST,
     EAST,
@@ -26,6 +24,51 @@ enum RayDirection {
     NOWEST,
 }
 
+pub const RAY_DIRECTION_COUNT: usize = 8;
+
+pub const RAY_DIRECTIONS: [RayDirection; RAY_DIRECTION_COUNT] = [
+    RayDirection::NORTH,
+    RayDirection::NOEAST,
+    RayDirection::EAST,
+    RayDirection::SOEAST,
+    RayDirection::SOUTH,
+    RayDirection::SOWEST,
+    RayDirection::WEST,
+    RayDirection::NOWEST,
+];
+
+impl RayDirection {
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    pub const fn opposite(self) -> RayDirection {
+        RAY_DIRECTIONS[(self.index() + RAY_DIRECTION_COUNT / 2) % RAY_DIRECTION_COUNT]
+    }
+
+    pub const fn is_positive(self) -> bool {
+        // Positive rays run towards higher square indices, so their nearest blocker is the lowest set bit.
+        matches!(
+            self,
+            RayDirection::NORTH | RayDirection::NOEAST | RayDirection::EAST | RayDirection::NOWEST
+        )
+    }
+}
+
+pub const BISHOP_DIRECTIONS: [RayDirection; 4] = [
+    RayDirection::NOEAST,
+    RayDirection::SOEAST,
+    RayDirection::SOWEST,
+    RayDirection::NOWEST,
+];
+
+pub const ROOK_DIRECTIONS: [RayDirection; 4] = [
+    RayDirection::NORTH,
+    RayDirection::EAST,
+    RayDirection::SOUTH,
+    RayDirection::WEST,
+];
+
 const fn calculate_south_ray_attack(origin: BitBoard) -> BitBoard {
     south_one(H_FILE) >> origin.leading_zeros()
 }
@@ -41,7 +84,7 @@ const fn calculate_west_ray_attack(origin: BitBoard) -> BitBoard {
     let not_rank_below: u64 = if shift_by < 64 {
         !(EIGHT_RANK >> shift_by)
     } else {
-        0
+        FULL_BOARD
     };
 
     source >> origin.leading_zeros() & not_rank_below
@@ -54,13 +97,14 @@ const fn calculate_east_ray_attack(origin: BitBoard) -> BitBoard {
     let not_rank_above: u64 = if shift_by < 64 {
         !(ONE_RANK << shift_by)
     } else {
-        0
+        FULL_BOARD
     };
 
     source << origin.trailing_zeros() & not_rank_above
 }
 
-const fn calculate_nowest_ray_attack(origin: BitBoard) -> BitBoard {
+// The diagonal shift helpers in bitboard.rs are named mirrored, north_west_one is the +9 step towards the h-file.
+const fn calculate_noeast_ray_attack(origin: BitBoard) -> BitBoard {
     let mut result: BitBoard = north_west_one(A1_H8_DIAG);
 
     let trailing_zeros = origin.trailing_zeros();
@@ -74,10 +118,10 @@ const fn calculate_nowest_ray_attack(origin: BitBoard) -> BitBoard {
         i += 1;
     }
 
-    result << rank_count * SQUARES_IN_RANK
+    result << (rank_count * SQUARES_IN_RANK)
 }
 
-const fn calculate_soeast_ray_attack(origin: BitBoard) -> BitBoard {
+const fn calculate_sowest_ray_attack(origin: BitBoard) -> BitBoard {
     let mut result: BitBoard = south_east_one(A1_H8_DIAG);
 
     let leading_zeros = origin.leading_zeros();
@@ -91,10 +135,10 @@ const fn calculate_soeast_ray_attack(origin: BitBoard) -> BitBoard {
         i += 1;
     }
 
-    result >> rank_count * SQUARES_IN_RANK
+    result >> (rank_count * SQUARES_IN_RANK)
 }
 
-const fn calculate_noeast_ray_attack(origin: BitBoard) -> BitBoard {
+const fn calculate_nowest_ray_attack(origin: BitBoard) -> BitBoard {
     let mut result: BitBoard = north_east_one(H1_A8_DIAG);
 
     let mut i = 0;
@@ -107,10 +151,10 @@ const fn calculate_noeast_ray_attack(origin: BitBoard) -> BitBoard {
         i += 1;
     }
 
-    result << rank_count * SQUARES_IN_RANK
+    result << (rank_count * SQUARES_IN_RANK)
 }
 
-const fn calculate_sowest_ray_attack(origin: BitBoard) -> BitBoard {
+const fn calculate_soeast_ray_attack(origin: BitBoard) -> BitBoard {
     let mut result: BitBoard = south_west_one(H1_A8_DIAG);
 
     let mut i = 0;
@@ -123,62 +167,149 @@ const fn calculate_sowest_ray_attack(origin: BitBoard) -> BitBoard {
         i += 1;
     }
 
-    result >> rank_count * SQUARES_IN_RANK
+    result >> (rank_count * SQUARES_IN_RANK)
+}
+
+pub type RayCollection = [BitBoard; RAY_DIRECTION_COUNT];
+
+pub const fn ray_collection(square: Square) -> RayCollection {
+    // Gives ray collection for the given square, indexed by RayDirection
+
+    let origin_bitboard: BitBoard = square.as_bitboard();
+    let mut result = [0; RAY_DIRECTION_COUNT];
+
+    result[RayDirection::NORTH.index()] = calculate_north_ray_attack(origin_bitboard);
+    result[RayDirection::SOUTH.index()] = calculate_south_ray_attack(origin_bitboard);
+    result[RayDirection::EAST.index()] = calculate_east_ray_attack(origin_bitboard);
+    result[RayDirection::WEST.index()] = calculate_west_ray_attack(origin_bitboard);
+    result[RayDirection::NOWEST.index()] = calculate_nowest_ray_attack(origin_bitboard);
+    result[RayDirection::NOEAST.index()] = calculate_noeast_ray_attack(origin_bitboard);
+    result[RayDirection::SOWEST.index()] = calculate_sowest_ray_attack(origin_bitboard);
+    result[RayDirection::SOEAST.index()] = calculate_soeast_ray_attack(origin_bitboard);
+
+    result
+}
+
+const fn calculate_ray_collection_for_each_square() -> BoardFlat<RayCollection> {
+    let mut result = board_flat([0; RAY_DIRECTION_COUNT]);
+    let mut i = 0;
+
+    while i < SQUARES.len() {
+        let square = SQUARES[i];
+        result[square as usize] = ray_collection(square);
+        i += 1;
+    }
+
+    result
 }
 
-type RayCollection = HashMap<RayDirection, BitBoard>;
-
-fn ray_collection(square_index: usize) -> RayCollection {
-    // Gives ray collection for square specified by square index
-
-    let origin_bitboard: BitBoard = Square::try_from(square_index as u64)
-        .expect("Cannot convert number to square")
-        .as_bitboard();
-
-    HashMap::from([
-        (
-            RayDirection::NORTH,
-            calculate_north_ray_attack(origin_bitboard),
-        ),
-        (
-            RayDirection::SOUTH,
-            calculate_south_ray_attack(origin_bitboard),
-        ),
-        (
-            RayDirection::WEST,
-            calculate_east_ray_attack(origin_bitboard),
-        ),
-        (
-            RayDirection::EAST,
-            calculate_west_ray_attack(origin_bitboard),
-        ),
-        (
-            RayDirection::NOWEST,
-            calculate_nowest_ray_attack(origin_bitboard),
-        ),
-        (
-            RayDirection::NOEAST,
-            calculate_noeast_ray_attack(origin_bitboard),
-        ),
-        (
-            RayDirection::SOWEST,
-            calculate_sowest_ray_attack(origin_bitboard),
-        ),
-        (
-            RayDirection::SOEAST,
-            calculate_soeast_ray_attack(origin_bitboard),
-        ),
-    ])
+pub static RAY_COLLECTIONS: BoardFlat<RayCollection> = calculate_ray_collection_for_each_square();
+
+pub type SquarePairTable = BoardFlat<BoardFlat<BitBoard>>;
+
+const fn calculate_between_and_line() -> (SquarePairTable, SquarePairTable) {
+    // For every pair of aligned squares, between holds the squares strictly in between and line
+    // the whole line through both. Pairs that share no line are left empty in both tables.
+    let rays = calculate_ray_collection_for_each_square();
+    let mut between = board_flat(board_flat(0));
+    let mut line = board_flat(board_flat(0));
+    let mut a = 0;
+
+    while a < SQUARES.len() {
+        let mut d = 0;
+
+        while d < RAY_DIRECTION_COUNT {
+            let direction = RAY_DIRECTIONS[d];
+            let forward = rays[a][direction.index()];
+            let full_line = forward | rays[a][direction.opposite().index()] | (1 << a);
+            let mut targets = forward;
+
+            while targets != 0 {
+                let b = targets.trailing_zeros() as usize;
+
+                between[a][b] = forward ^ rays[b][direction.index()] ^ (1 << b);
+                line[a][b] = full_line;
+                targets &= targets - 1;
+            }
+
+            d += 1;
+        }
+
+        a += 1;
+    }
+
+    (between, line)
+}
+
+static BETWEEN_AND_LINE: (SquarePairTable, SquarePairTable) = calculate_between_and_line();
+
+pub fn between(a: Square, b: Square) -> BitBoard {
+    BETWEEN_AND_LINE.0[a as usize][b as usize]
+}
+
+pub fn line(a: Square, b: Square) -> BitBoard {
+    BETWEEN_AND_LINE.1[a as usize][b as usize]
+}
+
+pub fn ray(square: Square, direction: RayDirection) -> BitBoard {
+    RAY_COLLECTIONS[square as usize][direction.index()]
+}
+
+pub fn ray_attacks(square: Square, occupancy: BitBoard, direction: RayDirection) -> BitBoard {
+    // The ray is cut behind the first blocker by removing the blocker's own ray in the same direction.
+    let ray = RAY_COLLECTIONS[square as usize][direction.index()];
+    let blockers = ray & occupancy;
+
+    if blockers == 0 {
+        return ray;
+    }
+
+    let first_blocker = if direction.is_positive() {
+        blockers.trailing_zeros()
+    } else {
+        63 - blockers.leading_zeros()
+    };
+
+    ray ^ RAY_COLLECTIONS[first_blocker as usize][direction.index()]
+}
+
+pub fn bishop_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+    BISHOP_DIRECTIONS.iter().fold(0, |acc, direction| {
+        acc | ray_attacks(square, occupancy, *direction)
+    })
+}
+
+pub fn rook_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+    ROOK_DIRECTIONS.iter().fold(0, |acc, direction| {
+        acc | ray_attacks(square, occupancy, *direction)
+    })
+}
+
+pub fn queen_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+    bishop_attacks(square, occupancy) | rook_attacks(square, occupancy)
 }
 
-fn calculate_ray_collection_for_each_square() -> BoardFlat<RayCollection> {
-    board_flat_non_copy(ray_collection)
+pub struct RayScan;
+
+impl SliderAttacks for RayScan {
+    fn rook_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        rook_attacks(square, occupancy)
+    }
+
+    fn bishop_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        bishop_attacks(square, occupancy)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{constants::SQUARE_COUNT, types::bitboard::bitboard_from_str};
+    use crate::{
+        attack_maps::ray_walk::{bishop_ray_walk, rook_ray_walk},
+        constants::SQUARE_COUNT,
+        types::bitboard::{bitboard_from_str, bitboard_to_str},
+    };
+    use strum::IntoEnumIterator;
 
     #[test]
     fn test_calculate_north_ray_attacks() {
@@ -303,7 +434,7 @@ mod test {
     }
 
     #[test]
-    fn test_calculate_north_west_ray_attack() {
+    fn test_calculate_north_east_ray_attack() {
         let correct_ray_attack = bitboard_from_str(
             "....x...
              ...x....
@@ -328,13 +459,13 @@ mod test {
         )
         .unwrap();
 
-        let calculated_ray_attack = calculate_nowest_ray_attack(d4);
+        let calculated_ray_attack = calculate_noeast_ray_attack(d4);
 
         assert_eq!(correct_ray_attack, calculated_ray_attack)
     }
 
     #[test]
-    fn test_calculate_south_east_ray_attack() {
+    fn test_calculate_south_west_ray_attack() {
         let correct_ray_attack = bitboard_from_str(
             "........
              ........
@@ -359,13 +490,13 @@ mod test {
         )
         .unwrap();
 
-        let calculated_ray_attack = calculate_soeast_ray_attack(position);
+        let calculated_ray_attack = calculate_sowest_ray_attack(position);
 
         assert_eq!(correct_ray_attack, calculated_ray_attack)
     }
 
     #[test]
-    fn test_calculate_north_east_ray_attack() {
+    fn test_calculate_north_west_ray_attack() {
         let correct_ray_attack = bitboard_from_str(
             "..x.....
              ...x....
@@ -390,13 +521,13 @@ mod test {
         )
         .unwrap();
 
-        let calculated_ray_attack = calculate_noeast_ray_attack(position);
+        let calculated_ray_attack = calculate_nowest_ray_attack(position);
 
         assert_eq!(correct_ray_attack, calculated_ray_attack)
     }
 
     #[test]
-    fn test_calculate_south_west_ray_attack() {
+    fn test_calculate_south_east_ray_attack() {
         let correct_ray_attack = bitboard_from_str(
             "........
              ........
@@ -421,7 +552,7 @@ mod test {
         )
         .unwrap();
 
-        let calculated_ray_attack = calculate_sowest_ray_attack(position);
+        let calculated_ray_attack = calculate_soeast_ray_attack(position);
 
         assert_eq!(correct_ray_attack, calculated_ray_attack)
     }
@@ -430,13 +561,120 @@ mod test {
     fn test_calculate_ray_collection_for_each_square() {
         let ray_collection_all = calculate_ray_collection_for_each_square();
 
-        assert_eq!(ray_collection_all.len(), SQUARE_COUNT as usize)
+        assert_eq!(ray_collection_all.len(), SQUARE_COUNT as usize);
+        assert_eq!(ray_collection_all, RAY_COLLECTIONS);
     }
 
     #[test]
     fn test_ray_collection() {
-        let ray_collection = ray_collection(55);
+        let ray_collection = ray_collection(Square::D4);
+
+        assert_eq!(
+            ray_collection[RayDirection::EAST.index()],
+            calculate_east_ray_attack(Square::D4.as_bitboard())
+        );
+        assert_eq!(
+            ray_collection[RayDirection::WEST.index()],
+            calculate_west_ray_attack(Square::D4.as_bitboard())
+        );
+        assert_eq!(ray(Square::H7, RayDirection::EAST), 0);
+    }
+
+    #[test]
+    fn test_east_and_west_rays_on_edge_ranks() {
+        assert_eq!(ray(Square::A8, RayDirection::EAST), EIGHT_RANK & !A_FILE);
+        assert_eq!(ray(Square::H1, RayDirection::WEST), ONE_RANK & !H_FILE);
+    }
+
+    #[test]
+    fn test_rook_attacks() {
+        let occupancy = bitboard_from_str(
+            "........
+             ...x....
+             ........
+             ........
+             .x.x..x.
+             ........
+             ........
+             ........",
+        )
+        .unwrap();
+
+        let correct = bitboard_from_str(
+            "........
+             ...x....
+             ...x....
+             ...x....
+             .xx.xxx.
+             ...x....
+             ...x....
+             ...x....",
+        )
+        .unwrap();
+
+        assert_eq!(correct, rook_attacks(Square::D4, occupancy));
+    }
+
+    #[test]
+    fn test_bishop_attacks() {
+        let occupancy = bitboard_from_str(
+            "........
+             ........
+             .....x..
+             ........
+             ...x....
+             ........
+             .x......
+             ........",
+        )
+        .unwrap();
+
+        let correct = bitboard_from_str(
+            "........
+             x.......
+             .x...x..
+             ..x.x...
+             ........
+             ..x.x...
+             .x...x..
+             ......x.",
+        )
+        .unwrap();
+
+        assert_eq!(correct, bishop_attacks(Square::D4, occupancy));
+    }
 
-        assert!(ray_collection.contains_key(&RayDirection::EAST))
+    #[test]
+    fn test_sliding_attacks_match_ray_walk() {
+        // Pseudo-random sparse and dense occupancies from a fixed xorshift sequence.
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for square in Square::iter() {
+            for _ in 0..64 {
+                let occupancy = next() & next();
+
+                assert_eq!(
+                    rook_attacks(square, occupancy),
+                    rook_ray_walk(square, occupancy)
+                );
+                assert_eq!(
+                    bishop_attacks(square, occupancy),
+                    bishop_ray_walk(square, occupancy)
+                );
+                assert_eq!(
+                    queen_attacks(square, occupancy),
+                    rook_ray_walk(square, occupancy) | bishop_ray_walk(square, occupancy)
+                );
+            }
+
+            assert_eq!(rook_attacks(square, 0), rook_ray_walk(square, 0));
+            assert_eq!(bishop_attacks(square, 0), bishop_ray_walk(square, 0));
+        }
     }
 }
@@ -0,0 +1,119 @@
+use crate::constants::ZERO;
+use crate::types::bitboard::{
+    east_one, north_east_one, north_one, north_west_one, south_east_one, south_one, south_west_one,
+    west_one, BitBoard,
+};
+use crate::types::square::Square;
+
+type Shift = fn(BitBoard) -> BitBoard;
+
+const ROOK_SHIFTS: [Shift; 4] = [north_one, south_one, east_one, west_one];
+const BISHOP_SHIFTS: [Shift; 4] = [
+    north_east_one,
+    north_west_one,
+    south_east_one,
+    south_west_one,
+];
+
+fn ray_walk(square: Square, occupancy: BitBoard, shifts: &[Shift]) -> BitBoard {
+    // Steps one square at a time in each direction, the first occupied square is still attacked.
+    let mut result = ZERO;
+
+    for shift in shifts {
+        let mut current = square.as_bitboard();
+
+        loop {
+            current = shift(current);
+            result |= current;
+
+            if current == ZERO || current & occupancy != ZERO {
+                break;
+            }
+        }
+    }
+
+    result
+}
+
+pub fn rook_ray_walk(square: Square, occupancy: BitBoard) -> BitBoard {
+    ray_walk(square, occupancy, &ROOK_SHIFTS)
+}
+
+pub fn bishop_ray_walk(square: Square, occupancy: BitBoard) -> BitBoard {
+    ray_walk(square, occupancy, &BISHOP_SHIFTS)
+}
+
+pub fn queen_ray_walk(square: Square, occupancy: BitBoard) -> BitBoard {
+    rook_ray_walk(square, occupancy) | bishop_ray_walk(square, occupancy)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::bitboard::bitboard_from_str;
+
+    #[test]
+    fn test_rook_ray_walk() {
+        let occupancy = bitboard_from_str(
+            "........
+             ...x....
+             ........
+             ........
+             .x.x..x.
+             ........
+             ........
+             ........",
+        )
+        .unwrap();
+
+        let correct = bitboard_from_str(
+            "........
+             ...x....
+             ...x....
+             ...x....
+             .xx.xxx.
+             ...x....
+             ...x....
+             ...x....",
+        )
+        .unwrap();
+
+        assert_eq!(correct, rook_ray_walk(Square::D4, occupancy));
+    }
+
+    #[test]
+    fn test_bishop_ray_walk() {
+        let occupancy = bitboard_from_str(
+            "........
+             ........
+             .....x..
+             ........
+             ...x....
+             ........
+             .x......
+             ........",
+        )
+        .unwrap();
+
+        let correct = bitboard_from_str(
+            "........
+             x.......
+             .x...x..
+             ..x.x...
+             ........
+             ..x.x...
+             .x...x..
+             ......x.",
+        )
+        .unwrap();
+
+        assert_eq!(correct, bishop_ray_walk(Square::D4, occupancy));
+    }
+
+    #[test]
+    fn test_ray_walk_on_empty_board_from_corner() {
+        assert_eq!(rook_ray_walk(Square::A1, ZERO).count_ones(), 14);
+        assert_eq!(bishop_ray_walk(Square::A1, ZERO).count_ones(), 7);
+        assert_eq!(queen_ray_walk(Square::H8, ZERO).count_ones(), 21);
+    }
+}
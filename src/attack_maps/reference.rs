@@ -0,0 +1,207 @@
+// Slow reference generators working on (file, rank) coordinates. They share no code with the
+// table generators, so every precomputed table can be checked against them square by square.
+
+use crate::attack_maps::king_attacks::KING_ATTACKS;
+use crate::attack_maps::knight_attacks::KNIGHT_ATTACKS;
+use crate::attack_maps::pawn_attack::PAWN_ATTACKS;
+use crate::attack_maps::ray_attacks::{
+    between, line, RayDirection, RAY_COLLECTIONS, RAY_DIRECTIONS,
+};
+use crate::piece::Color;
+use crate::types::bitboard::{bitboard_to_str, BitBoard};
+use crate::types::square::{Square, SQUARES};
+
+type Step = (i64, i64);
+
+const KING_STEPS: [Step; 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+const KNIGHT_STEPS: [Step; 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+fn coordinates(square: Square) -> Step {
+    (square as i64 % 8, square as i64 / 8)
+}
+
+fn square_at(file: i64, rank: i64) -> Option<Square> {
+    if (0..8).contains(&file) && (0..8).contains(&rank) {
+        Square::try_from((rank * 8 + file) as u64).ok()
+    } else {
+        None
+    }
+}
+
+fn direction_step(direction: RayDirection) -> Step {
+    // Compass steps as (file, rank), with east towards the h-file and north towards the eighth rank.
+    match direction {
+        RayDirection::NORTH => (0, 1),
+        RayDirection::NOEAST => (1, 1),
+        RayDirection::EAST => (1, 0),
+        RayDirection::SOEAST => (1, -1),
+        RayDirection::SOUTH => (0, -1),
+        RayDirection::SOWEST => (-1, -1),
+        RayDirection::WEST => (-1, 0),
+        RayDirection::NOWEST => (-1, 1),
+    }
+}
+
+fn leaper_attacks(square: Square, steps: &[Step]) -> BitBoard {
+    let (file, rank) = coordinates(square);
+
+    steps
+        .iter()
+        .filter_map(|(df, dr)| square_at(file + df, rank + dr))
+        .fold(0, |acc, target| acc | target.as_bitboard())
+}
+
+fn ray(square: Square, (df, dr): Step) -> BitBoard {
+    let (mut file, mut rank) = coordinates(square);
+    let mut result = 0;
+
+    while let Some(target) = square_at(file + df, rank + dr) {
+        result |= target.as_bitboard();
+        file += df;
+        rank += dr;
+    }
+
+    result
+}
+
+fn alignment(a: Square, b: Square) -> Option<Step> {
+    let (file_a, rank_a) = coordinates(a);
+    let (file_b, rank_b) = coordinates(b);
+    let (df, dr) = (file_b - file_a, rank_b - rank_a);
+
+    if a != b && (df == 0 || dr == 0 || df.abs() == dr.abs()) {
+        Some((df.signum(), dr.signum()))
+    } else {
+        None
+    }
+}
+
+fn reference_between(a: Square, b: Square) -> BitBoard {
+    match alignment(a, b) {
+        Some(step) => ray(a, step) & !ray(b, step) & !b.as_bitboard(),
+        None => 0,
+    }
+}
+
+fn reference_line(a: Square, b: Square) -> BitBoard {
+    match alignment(a, b) {
+        Some((df, dr)) => ray(a, (df, dr)) | ray(a, (-df, -dr)) | a.as_bitboard(),
+        None => 0,
+    }
+}
+
+fn assert_table_entry(table: &str, square: Square, expected: BitBoard, actual: BitBoard) {
+    assert_eq!(
+        expected,
+        actual,
+        "{} on {}\nexpected:\n{}\nactual:\n{}",
+        table,
+        square,
+        bitboard_to_str(expected),
+        bitboard_to_str(actual)
+    );
+}
+
+#[test]
+fn test_king_attacks_match_reference() {
+    for square in SQUARES {
+        assert_table_entry(
+            "KING_ATTACKS",
+            square,
+            leaper_attacks(square, &KING_STEPS),
+            KING_ATTACKS[square as usize],
+        );
+    }
+}
+
+#[test]
+fn test_knight_attacks_match_reference() {
+    for square in SQUARES {
+        assert_table_entry(
+            "KNIGHT_ATTACKS",
+            square,
+            leaper_attacks(square, &KNIGHT_STEPS),
+            KNIGHT_ATTACKS[square as usize],
+        );
+    }
+}
+
+#[test]
+fn test_pawn_attacks_match_reference() {
+    for square in SQUARES {
+        assert_table_entry(
+            "white PAWN_ATTACKS",
+            square,
+            leaper_attacks(square, &[(-1, 1), (1, 1)]),
+            PAWN_ATTACKS[Color::WHITE.index()][square as usize],
+        );
+        assert_table_entry(
+            "black PAWN_ATTACKS",
+            square,
+            leaper_attacks(square, &[(-1, -1), (1, -1)]),
+            PAWN_ATTACKS[Color::BLACK.index()][square as usize],
+        );
+    }
+}
+
+#[test]
+fn test_ray_collections_match_reference() {
+    for square in SQUARES {
+        for direction in RAY_DIRECTIONS {
+            assert_table_entry(
+                &format!("{:?} ray", direction),
+                square,
+                ray(square, direction_step(direction)),
+                RAY_COLLECTIONS[square as usize][direction.index()],
+            );
+        }
+    }
+}
+
+#[test]
+fn test_squares_cover_the_board_in_order() {
+    for (i, square) in SQUARES.iter().enumerate() {
+        assert_eq!(*square as usize, i);
+    }
+}
+
+#[test]
+fn test_between_and_line_match_reference() {
+    for a in SQUARES {
+        for b in SQUARES {
+            let pair = format!("pair with {}", b);
+
+            assert_table_entry(
+                &format!("between {}", pair),
+                a,
+                reference_between(a, b),
+                between(a, b),
+            );
+            assert_table_entry(
+                &format!("line {}", pair),
+                a,
+                reference_line(a, b),
+                line(a, b),
+            );
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::types::bitboard::BitBoard;
+use crate::types::square::Square;
+
+pub trait SliderAttacks {
+    fn rook_attacks(square: Square, occupancy: BitBoard) -> BitBoard;
+
+    fn bishop_attacks(square: Square, occupancy: BitBoard) -> BitBoard;
+
+    fn queen_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+        Self::rook_attacks(square, occupancy) | Self::bishop_attacks(square, occupancy)
+    }
+}
+
+// The backend used by move generation is picked by cargo feature, magic bitboards otherwise.
+// When several features are enabled the first match in the order below wins.
+#[cfg(feature = "slider-pext")]
+pub type DefaultSliders = crate::attack_maps::pext::Pext;
+
+#[cfg(all(feature = "slider-hyperbola", not(feature = "slider-pext")))]
+pub type DefaultSliders = crate::attack_maps::hyperbola::HyperbolaQuintessence;
+
+#[cfg(all(
+    feature = "slider-kogge-stone",
+    not(any(feature = "slider-pext", feature = "slider-hyperbola"))
+))]
+pub type DefaultSliders = crate::attack_maps::kogge_stone::KoggeStone;
+
+#[cfg(not(any(
+    feature = "slider-pext",
+    feature = "slider-hyperbola",
+    feature = "slider-kogge-stone"
+)))]
+pub type DefaultSliders = crate::attack_maps::magic::Magic;
+
+pub fn rook_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+    DefaultSliders::rook_attacks(square, occupancy)
+}
+
+pub fn bishop_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+    DefaultSliders::bishop_attacks(square, occupancy)
+}
+
+pub fn queen_attacks(square: Square, occupancy: BitBoard) -> BitBoard {
+    DefaultSliders::queen_attacks(square, occupancy)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::attack_maps::hyperbola::HyperbolaQuintessence;
+    use crate::attack_maps::kogge_stone::KoggeStone;
+    use crate::attack_maps::magic::{occupancy_subsets, relevant_occupancy_mask, Magic, Slider};
+    use crate::attack_maps::pext::Pext;
+    use crate::attack_maps::ray_attacks::RayScan;
+    use crate::attack_maps::ray_walk::{bishop_ray_walk, rook_ray_walk};
+    use strum::IntoEnumIterator;
+
+    fn assert_backend_agrees<T: SliderAttacks>(name: &str, square: Square, occupancy: BitBoard) {
+        assert_eq!(
+            T::rook_attacks(square, occupancy),
+            rook_ray_walk(square, occupancy),
+            "{} rook attacks from {} with occupancy {:#x}",
+            name,
+            square,
+            occupancy
+        );
+        assert_eq!(
+            T::bishop_attacks(square, occupancy),
+            bishop_ray_walk(square, occupancy),
+            "{} bishop attacks from {} with occupancy {:#x}",
+            name,
+            square,
+            occupancy
+        );
+    }
+
+    fn assert_all_backends_agree(square: Square, occupancy: BitBoard) {
+        assert_backend_agrees::<RayScan>("ray scan", square, occupancy);
+        assert_backend_agrees::<Magic>("magic", square, occupancy);
+        assert_backend_agrees::<HyperbolaQuintessence>("hyperbola", square, occupancy);
+        assert_backend_agrees::<KoggeStone>("kogge-stone", square, occupancy);
+        assert_backend_agrees::<Pext>("pext", square, occupancy);
+    }
+
+    #[test]
+    fn test_backends_agree_on_random_occupancies() {
+        let mut state: u64 = 0x0123_4567_89AB_CDEF;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for square in Square::iter() {
+            assert_all_backends_agree(square, 0);
+            assert_all_backends_agree(square, !0);
+
+            for _ in 0..256 {
+                let sparse = next() & next();
+                let dense = next() | next();
+
+                assert_all_backends_agree(square, sparse);
+                assert_all_backends_agree(square, dense);
+            }
+        }
+    }
+
+    #[test]
+    fn test_backends_agree_on_every_relevant_subset() {
+        for square in Square::iter() {
+            for slider in [Slider::ROOK, Slider::BISHOP] {
+                for occupancy in occupancy_subsets(relevant_occupancy_mask(slider, square)) {
+                    assert_all_backends_agree(square, occupancy);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_backend_queen_attacks() {
+        for square in Square::iter() {
+            assert_eq!(
+                queen_attacks(square, 0),
+                rook_ray_walk(square, 0) | bishop_ray_walk(square, 0)
+            );
+        }
+    }
+}
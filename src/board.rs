@@ -1,12 +1,19 @@
-use int_enum::IntEnum;
-use std::collections::HashMap;
-use std::iter::{repeat, zip};
 use strum::IntoEnumIterator;
 
+use crate::attack_maps::king_attacks::KING_ATTACKS;
+use crate::attack_maps::knight_attacks::KNIGHT_ATTACKS;
+use crate::attack_maps::pawn_attack::pawn_any_attacks;
+use crate::attack_maps::sliders::{bishop_attacks, rook_attacks};
 use crate::constants::{FILE_COUNT, ONE, RANK_COUNT, SQUARE_COUNT, ZERO};
-use crate::piece::{Piece, PIECE_SET};
-use crate::types::bitboard::{square_occupied, BitBoard};
+use crate::fen::FenError;
+use crate::piece::{
+    Color::{self, BLACK, WHITE},
+    Piece::{self, *},
+    COLOR_COUNT, PIECE_COUNT,
+};
+use crate::types::bitboard::{bitboard_squares, square_occupied, BitBoard};
 use crate::types::file::File;
+use crate::types::rank::Rank;
 use crate::types::square::Square;
 
 pub type BoardSerialized<T> = [[T; RANK_COUNT as usize]; FILE_COUNT as usize];
@@ -14,6 +21,7 @@ pub const fn board_serialized<T: Copy>(arg: T) -> BoardSerialized<T> {
     [[arg; RANK_COUNT as usize]; FILE_COUNT as usize]
 }
 
+#[cfg(test)]
 fn rotate_serialized_board<T: Copy>(mut board: BoardSerialized<T>) -> BoardSerialized<T> {
     board.reverse();
     board.iter_mut().for_each(|rank| rank.reverse());
@@ -24,71 +32,141 @@ pub type BoardFlat<T> = [T; SQUARE_COUNT as usize];
 pub const fn board_flat<T: Copy>(arg: T) -> BoardFlat<T> {
     [arg; SQUARE_COUNT as usize]
 }
-pub fn board_flat_non_copy<T, F>(mut cb: F) -> BoardFlat<T>
+pub fn board_flat_non_copy<T, F>(cb: F) -> BoardFlat<T>
 where
     F: FnMut(usize) -> T,
 {
-    std::array::from_fn(|i| cb(i))
+    std::array::from_fn(cb)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Board {
-    bit_boards: HashMap<Piece, BitBoard>,
+    bit_boards: [BitBoard; PIECE_COUNT],
+    color_bit_boards: [BitBoard; COLOR_COUNT],
+    all_bit_board: BitBoard,
+    mailbox: BoardFlat<Option<Piece>>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
 }
 
 impl Board {
     pub fn new() -> Board {
         Board {
-            bit_boards: HashMap::from_iter(zip(PIECE_SET, repeat(ZERO))),
+            bit_boards: [ZERO; PIECE_COUNT],
+            color_bit_boards: [ZERO; COLOR_COUNT],
+            all_bit_board: ZERO,
+            mailbox: board_flat(None),
         }
     }
 
-    fn piece_set() -> &'static [Piece] {
-        &PIECE_SET
-    }
-
     pub fn piece_bit_board(&self, piece: Piece) -> BitBoard {
-        *self.bit_boards.get(&piece).unwrap()
+        self.bit_boards[piece.index()]
     }
 
-    fn piece_bit_board_mut(&mut self, piece: Piece) -> BitBoard {
-        *self.bit_boards.get_mut(&piece).unwrap()
-    }
+    pub fn set_piece_bit_board(&mut self, piece: Piece, bitboard: BitBoard) {
+        let color = piece.color().index();
+        let old = self.bit_boards[piece.index()];
 
-    pub fn set_piece_bit_board(&mut self, piece: Piece, bitboard: BitBoard) -> () {
-        self.bit_boards.insert(piece, bitboard);
-    }
+        for square in bitboard_squares(old) {
+            self.mailbox[square as usize] = None;
+        }
+        self.color_bit_boards[color] &= !old;
 
-    pub fn all_bit_boards(&self) -> BitBoard {
-        let mut result: u64 = ZERO;
+        // Squares taken over from other pieces are cleared from their boards, so all arrays stay in sync.
+        for bit_board in self.bit_boards.iter_mut() {
+            *bit_board &= !bitboard;
+        }
+        for color_bit_board in self.color_bit_boards.iter_mut() {
+            *color_bit_board &= !bitboard;
+        }
 
-        for bitboard in self.bit_boards.values() {
-            result = result | bitboard;
+        for square in bitboard_squares(bitboard) {
+            self.mailbox[square as usize] = Some(piece);
         }
+        self.color_bit_boards[color] |= bitboard;
 
-        result
+        self.bit_boards[piece.index()] = bitboard;
+        self.all_bit_board =
+            self.color_bit_boards[WHITE.index()] | self.color_bit_boards[BLACK.index()];
     }
 
-    pub fn put_piece_on_square(&mut self, piece: Piece, square: Square) -> Result<(), &str> {
-        let mut bboard = self.piece_bit_board_mut(piece);
+    pub fn color_bit_board(&self, color: Color) -> BitBoard {
+        self.color_bit_boards[color.index()]
+    }
+
+    pub fn all_bit_boards(&self) -> BitBoard {
+        self.all_bit_board
+    }
 
-        if square_occupied(bboard, square) {
-            eprintln!("Square {:?} occupied", square);
-            return Err("Square occupied");
+    pub fn put_piece_on_square(&mut self, piece: Piece, square: Square) -> Result<(), &str> {
+        if square_occupied(self.all_bit_board, square) {
+            Err("Square occupied")
         } else {
-            bboard = bboard | (ONE << square as u64);
-            self.set_piece_bit_board(piece, bboard);
+            let bit = ONE << square as u64;
+
+            self.bit_boards[piece.index()] |= bit;
+            self.color_bit_boards[piece.color().index()] |= bit;
+            self.all_bit_board |= bit;
+            self.mailbox[square as usize] = Some(piece);
             Ok(())
         }
     }
 
+    pub fn remove_piece_from_square(&mut self, square: Square) -> Option<Piece> {
+        let piece = self.mailbox[square as usize]?;
+        let bit = ONE << square as u64;
+
+        self.bit_boards[piece.index()] &= !bit;
+        self.color_bit_boards[piece.color().index()] &= !bit;
+        self.all_bit_board &= !bit;
+        self.mailbox[square as usize] = None;
+
+        Some(piece)
+    }
+
     pub fn piece_on_square(&self, square: Square) -> Option<Piece> {
-        for (piece, bitboard) in &self.bit_boards {
-            if square_occupied(*bitboard, square) {
-                return Some(*piece);
+        self.mailbox[square as usize]
+    }
+
+    pub fn attackers_to(&self, square: Square, occupancy: BitBoard) -> BitBoard {
+        // Pieces of both colors attacking the square, sliders are blocked by the given occupancy.
+        self.attackers_by(square, WHITE, occupancy) | self.attackers_by(square, BLACK, occupancy)
+    }
+
+    pub fn attackers_by(&self, square: Square, by: Color, occupancy: BitBoard) -> BitBoard {
+        // Only the pieces of `by`, a pawn attacks the square if a pawn of the other color standing
+        // there would attack it.
+        let origin = square.as_bitboard();
+        let queens = self.piece_bit_board(QUEEN(by));
+
+        pawn_any_attacks(by.oposite(), origin) & self.piece_bit_board(PAWN(by))
+            | KNIGHT_ATTACKS[square as usize] & self.piece_bit_board(KNIGHT(by))
+            | KING_ATTACKS[square as usize] & self.piece_bit_board(KING(by))
+            | bishop_attacks(square, occupancy) & (self.piece_bit_board(BISHOP(by)) | queens)
+            | rook_attacks(square, occupancy) & (self.piece_bit_board(ROOK(by)) | queens)
+    }
+
+    pub fn attacked_by(&self, color: Color) -> BitBoard {
+        let occupancy = self.all_bit_board;
+        let mut result = pawn_any_attacks(color, self.piece_bit_board(PAWN(color)));
+
+        for piece in [
+            KNIGHT(color),
+            BISHOP(color),
+            ROOK(color),
+            QUEEN(color),
+            KING(color),
+        ] {
+            for square in bitboard_squares(self.piece_bit_board(piece)) {
+                result |= piece.attacks(square, occupancy);
             }
         }
 
-        None
+        result
     }
 
     pub fn piecewise_representation(&self) -> BoardSerialized<Option<Piece>> {
@@ -110,55 +188,79 @@ impl Board {
     }
 }
 
-pub fn fill_board_fen(board: &mut Board, fen_string: &str) -> () {
-    let mut i: u64 = 0;
+pub fn fill_board_fen(board: &mut Board, fen_string: &str) -> Result<(), FenError> {
+    // Only the piece placement field is read, so both a bare placement and a full FEN are accepted.
+    let placement = fen_string.split_whitespace().next().unwrap_or("");
+    let ranks: Vec<&str> = placement.split('/').collect();
 
-    for c in fen_string.replace("/", "").chars().rev() {
-        if c.is_numeric() {
-            i += c.to_digit(10).unwrap_or(0) as u64;
-        } else {
-            board
-                .put_piece_on_square(Piece::from_char(c).unwrap(), Square::from_int(i).unwrap())
-                .unwrap();
-            i += 1;
+    if ranks.len() != RANK_COUNT as usize {
+        return Err(FenError::InvalidRankCount(ranks.len()));
+    }
+
+    for (i, rank_str) in ranks.iter().enumerate() {
+        let rank = Rank::try_from((RANK_COUNT as usize - 1 - i) as u64).unwrap();
+        let mut file: u32 = 0;
+
+        for c in rank_str.chars() {
+            if let Some(empty) = c.to_digit(10).filter(|d| (1..=FILE_COUNT).contains(d)) {
+                file += empty;
+            } else {
+                let piece = Piece::from_char(c).map_err(|_| FenError::InvalidPiece(c))?;
+
+                if file >= FILE_COUNT {
+                    return Err(FenError::InvalidRankLength(rank));
+                }
+
+                let square = Square::from_file_rank(File::try_from(file as u64).unwrap(), rank);
+                board
+                    .put_piece_on_square(piece, square)
+                    .map_err(|_| FenError::SquareOccupied(square))?;
+                file += 1;
+            }
+        }
+
+        if file != FILE_COUNT {
+            return Err(FenError::InvalidRankLength(rank));
         }
     }
+
+    Ok(())
 }
 
-pub fn print_board(board: &Board) -> () {
+pub fn board_to_str(board: &Board) -> String {
     let piece_bit_board = board.piecewise_representation();
-
-    print!("  _________________\n");
+    let mut result = String::from("  _________________\n");
 
     for (i, rank) in piece_bit_board.iter().rev().enumerate() {
-        print!("{} ", FILE_COUNT as usize - i);
+        result += &format!("{} ", FILE_COUNT as usize - i);
 
         for piece_opt in rank {
             match piece_opt {
-                Some(piece) => print!("|{}", piece.to_char()),
-                None => print!("| "),
+                Some(piece) => result += &format!("|{}", piece.to_char()),
+                None => result += "| ",
             }
         }
-        print!("|");
-        print!("\n  -----------------\n");
+        result += "|\n  -----------------\n";
     }
 
-    print!("   ");
+    result += "   ";
     for i in 0..FILE_COUNT {
         let file = File::try_from(i as u64).unwrap();
-        print!("{} ", file.to_char());
+        result += &format!("{} ", file.to_char());
     }
 
-    print!("\n\n\n")
+    result
+}
+
+pub fn print_board(board: &Board) {
+    print!("{}\n\n\n", board_to_str(board))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::constants::{DEFAULT_FEN, ONE};
-    use crate::piece::Color::{BLACK, WHITE};
+    use crate::constants::{A_FILE, DEFAULT_FEN, ONE, ONE_RANK};
     use crate::piece::Piece;
 
-    use super::Piece::*;
     use super::Square::*;
     use super::*;
 
@@ -170,7 +272,7 @@ mod test {
         unrotated[7][0] = 3; // .......
         unrotated[7][7] = 4; // 3.....4
 
-        let rotated = rotate_serialized_board(unrotated.clone());
+        let rotated = rotate_serialized_board(unrotated);
         // 4.....3
         // .......
         // .......
@@ -191,6 +293,72 @@ mod test {
 
         assert_eq!(board.piece_bit_board(ROOK(BLACK)), ONE << C6 as u64);
         assert!(board.put_piece_on_square(ROOK(BLACK), C6).is_err());
+        assert!(board.put_piece_on_square(QUEEN(WHITE), C6).is_err());
+
+        assert_eq!(board.piece_on_square(C6), Some(ROOK(BLACK)));
+        assert_eq!(board.color_bit_board(BLACK), ONE << C6 as u64);
+        assert_eq!(board.color_bit_board(WHITE), 0);
+        assert_eq!(board.all_bit_boards(), ONE << C6 as u64);
+    }
+
+    #[test]
+    fn test_remove_piece_from_square() {
+        let mut board = Board::from_fen(DEFAULT_FEN).unwrap();
+
+        assert_eq!(board.remove_piece_from_square(E2), Some(PAWN(WHITE)));
+        assert_eq!(board.remove_piece_from_square(E2), None);
+        assert_eq!(board.piece_on_square(E2), None);
+        assert!(!square_occupied(board.piece_bit_board(PAWN(WHITE)), E2));
+        assert!(!square_occupied(board.color_bit_board(WHITE), E2));
+        assert!(!square_occupied(board.all_bit_boards(), E2));
+    }
+
+    #[test]
+    fn test_set_piece_bit_board_keeps_occupancy_in_sync() {
+        let mut board = Board::from_fen(DEFAULT_FEN).unwrap();
+        let e4 = ONE << E4 as u64;
+
+        board.set_piece_bit_board(PAWN(WHITE), e4);
+
+        assert_eq!(board.piece_on_square(E4), Some(PAWN(WHITE)));
+        assert_eq!(board.piece_on_square(E2), None);
+        assert_eq!(board.color_bit_board(WHITE), e4 | 0xFF);
+        assert_eq!(
+            board.all_bit_boards(),
+            board.color_bit_board(WHITE) | board.color_bit_board(BLACK)
+        );
+    }
+
+    #[test]
+    fn test_set_piece_bit_board_over_other_pieces() {
+        let mut board = Board::from_fen(DEFAULT_FEN).unwrap();
+        let e1_e8 = E1.as_bitboard() | E8.as_bitboard();
+
+        board.set_piece_bit_board(QUEEN(WHITE), e1_e8);
+
+        assert_eq!(board.piece_on_square(E1), Some(QUEEN(WHITE)));
+        assert_eq!(board.piece_on_square(E8), Some(QUEEN(WHITE)));
+        assert_eq!(board.piece_on_square(D1), None);
+        assert_eq!(board.piece_bit_board(KING(WHITE)), ZERO);
+        assert_eq!(board.piece_bit_board(KING(BLACK)), ZERO);
+        assert!(square_occupied(board.color_bit_board(WHITE), E8));
+        assert!(!square_occupied(board.color_bit_board(BLACK), E8));
+
+        for square in Square::iter() {
+            let piece = board.piece_on_square(square);
+
+            assert_eq!(
+                piece.is_some(),
+                square_occupied(board.all_bit_boards(), square)
+            );
+            if let Some(piece) = piece {
+                assert!(square_occupied(board.piece_bit_board(piece), square));
+                assert!(square_occupied(
+                    board.color_bit_board(piece.color()),
+                    square
+                ));
+            }
+        }
     }
 
     #[test]
@@ -215,9 +383,9 @@ mod test {
     #[test]
     fn test_fill_board_fen() {
         let mut board = Board::new();
-        fill_board_fen(&mut board, DEFAULT_FEN);
+        fill_board_fen(&mut board, DEFAULT_FEN).unwrap();
 
-        let starting_position = [
+        let mut starting_position = [
             [
                 Some(ROOK(BLACK)),
                 Some(KNIGHT(BLACK)),
@@ -264,9 +432,115 @@ mod test {
             ],
         ];
 
+        // Ranks are listed from the eighth down, files from a to h, exactly as in a FEN.
+        starting_position.reverse();
+
+        assert_eq!(starting_position, board.piecewise_representation());
+    }
+
+    #[test]
+    fn test_fill_board_fen_errors() {
+        let mut board = Board::new();
+        assert_eq!(
+            fill_board_fen(&mut board, "8/8/8/8/8/8/8"),
+            Err(FenError::InvalidRankCount(7))
+        );
+
+        let mut board = Board::new();
+        assert_eq!(
+            fill_board_fen(&mut board, "8/8/8/8/8/8/8/9"),
+            Err(FenError::InvalidPiece('9'))
+        );
+
+        let mut board = Board::new();
+        assert_eq!(
+            fill_board_fen(&mut board, "8/8/8/8/8/8/8/ppppppppp"),
+            Err(FenError::InvalidRankLength(Rank::ONE))
+        );
+    }
+
+    #[test]
+    fn test_attackers_to() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R").unwrap();
+        let occupancy = board.all_bit_boards();
+
+        // d5 is hit by the e4 pawn, c3 knight and f3 queen for white, the e6 pawn, b6 and
+        // f6 knights for black.
         assert_eq!(
-            rotate_serialized_board(starting_position),
-            board.piecewise_representation()
+            board.attackers_to(D5, occupancy),
+            E4.as_bitboard()
+                | C3.as_bitboard()
+                | E6.as_bitboard()
+                | B6.as_bitboard()
+                | F6.as_bitboard()
         );
+
+        // Both rooks along the first rank and the d2 bishop. Clearing the e-file in the
+        // occupancy lets the e7 queen see e1 as well.
+        let defenders = A1.as_bitboard() | H1.as_bitboard() | D2.as_bitboard();
+        assert_eq!(board.attackers_to(E1, occupancy), defenders);
+        assert_eq!(
+            board.attackers_to(
+                E1,
+                occupancy
+                    & !E2.as_bitboard()
+                    & !E4.as_bitboard()
+                    & !E5.as_bitboard()
+                    & !E6.as_bitboard()
+            ),
+            defenders | E7.as_bitboard()
+        );
+    }
+
+    #[test]
+    fn test_attacked_by() {
+        let board = Board::from_fen(DEFAULT_FEN).unwrap();
+
+        assert_eq!(board.attacked_by(WHITE), 0x0000_0000_00FF_FF7E);
+        assert_eq!(board.attacked_by(BLACK), 0x7EFF_FF00_0000_0000);
+
+        let board = Board::from_fen("8/8/8/3k4/8/8/8/R3K3").unwrap();
+        let rook = (ONE_RANK | A_FILE)
+            & !A1.as_bitboard()
+            & !F1.as_bitboard()
+            & !G1.as_bitboard()
+            & !H1.as_bitboard();
+
+        assert_eq!(board.attacked_by(WHITE), rook | KING_ATTACKS[E1 as usize]);
+    }
+
+    #[test]
+    fn test_attacked_by_matches_attackers_to() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R").unwrap();
+
+        for color in [WHITE, BLACK] {
+            let expected = Square::iter()
+                .filter(|square| {
+                    board.attackers_to(*square, board.all_bit_boards())
+                        & board.color_bit_board(color)
+                        != ZERO
+                })
+                .fold(ZERO, |acc, square| acc | square.as_bitboard());
+
+            assert_eq!(board.attacked_by(color), expected);
+        }
+    }
+
+    #[test]
+    fn test_attackers_by_matches_attackers_to() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R").unwrap();
+        let occupancy = board.all_bit_boards();
+
+        for square in Square::iter() {
+            for color in [WHITE, BLACK] {
+                assert_eq!(
+                    board.attackers_by(square, color, occupancy),
+                    board.attackers_to(square, occupancy) & board.color_bit_board(color)
+                );
+            }
+        }
     }
 }
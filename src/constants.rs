@@ -2,7 +2,7 @@ use crate::types::bitboard::BitBoard;
 
 pub const ONE: u64 = 1;
 pub const ZERO: u64 = 0;
-pub const DEFAULT_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+pub const DEFAULT_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
 pub const SQUARES_IN_RANK: u32 = 8;
 pub const RANK_COUNT: u32 = 8;
@@ -0,0 +1,383 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::board::Board;
+use crate::constants::SQUARE_COUNT;
+use crate::pawns::{evaluate_pawns, PawnTable};
+use crate::piece::{Color, Piece, Piece::*, PIECE_COUNT, PIECE_SET};
+use crate::position::Position;
+use crate::types::bitboard::bitboard_squares;
+use crate::types::square::Square;
+
+// Exchange values used by move ordering and SEE, the evaluation has its own below.
+pub const PAWN_VALUE: i32 = 100;
+pub const KNIGHT_VALUE: i32 = 320;
+pub const BISHOP_VALUE: i32 = 330;
+pub const ROOK_VALUE: i32 = 500;
+pub const QUEEN_VALUE: i32 = 900;
+
+pub const fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        PAWN(_) => PAWN_VALUE,
+        KNIGHT(_) => KNIGHT_VALUE,
+        BISHOP(_) => BISHOP_VALUE,
+        ROOK(_) => ROOK_VALUE,
+        QUEEN(_) => QUEEN_VALUE,
+        KING(_) => 0,
+    }
+}
+
+// A middlegame and an endgame score, blended by the game phase when evaluating.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tapered {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Tapered {
+    pub const ZERO: Tapered = Tapered::new(0, 0);
+
+    pub const fn new(mg: i32, eg: i32) -> Tapered {
+        Tapered { mg, eg }
+    }
+
+    // Weighs mg by phase and eg by what is left of MAX_PHASE.
+    pub fn blend(self, phase: i32) -> i32 {
+        let phase = phase.min(MAX_PHASE);
+
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Tapered {
+    type Output = Tapered;
+
+    fn add(self, other: Tapered) -> Tapered {
+        Tapered::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl Sub for Tapered {
+    type Output = Tapered;
+
+    fn sub(self, other: Tapered) -> Tapered {
+        Tapered::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Mul<i32> for Tapered {
+    type Output = Tapered;
+
+    fn mul(self, factor: i32) -> Tapered {
+        Tapered::new(self.mg * factor, self.eg * factor)
+    }
+}
+
+impl Neg for Tapered {
+    type Output = Tapered;
+
+    fn neg(self) -> Tapered {
+        Tapered::new(-self.mg, -self.eg)
+    }
+}
+
+impl AddAssign for Tapered {
+    fn add_assign(&mut self, other: Tapered) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Tapered {
+    fn sub_assign(&mut self, other: Tapered) {
+        *self = *self - other;
+    }
+}
+
+// Indexed by piece kind: pawn, knight, bishop, rook, queen, king.
+const MG_VALUES: [i32; 6] = [82, 337, 365, 477, 1025, 0];
+const EG_VALUES: [i32; 6] = [94, 281, 297, 512, 936, 0];
+
+// Phase contributed by each piece kind, the starting position adds up to MAX_PHASE.
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+pub const MAX_PHASE: i32 = 24;
+
+type Table = [i32; SQUARE_COUNT as usize];
+
+// Piece-square tables from white's point of view, laid out as seen from white: a8 comes first.
+#[rustfmt::skip]
+const MG_TABLES: [Table; 6] = [
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         98, 134,  61,  95,  68, 126,  34, -11,
+         -6,   7,  26,  31,  65,  56,  25, -20,
+        -14,  13,   6,  21,  23,  12,  17, -23,
+        -27,  -2,  -5,  12,  17,   6,  10, -25,
+        -26,  -4,  -4, -10,   3,   3,  33, -12,
+        -35,  -1, -20, -23, -15,  24,  38, -22,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    [
+       -167, -89, -34, -49,  61, -97, -15,-107,
+        -73, -41,  72,  36,  23,  62,   7, -17,
+        -47,  60,  37,  65,  84, 129,  73,  44,
+         -9,  17,  19,  53,  37,  69,  18,  22,
+        -13,   4,  16,  13,  28,  19,  21,  -8,
+        -23,  -9,  12,  10,  19,  17,  25, -16,
+        -29, -53, -12,  -3,  -1,  18, -14, -19,
+       -105, -21, -58, -33, -17, -28, -19, -23,
+    ],
+    [
+        -29,   4, -82, -37, -25, -42,   7,  -8,
+        -26,  16, -18, -13,  30,  59,  18, -47,
+        -16,  37,  43,  40,  35,  50,  37,  -2,
+         -4,   5,  19,  50,  37,  37,   7,  -2,
+         -6,  13,  13,  26,  34,  12,  10,   4,
+          0,  15,  15,  15,  14,  27,  18,  10,
+          4,  15,  16,   0,   7,  21,  33,   1,
+        -33,  -3, -14, -21, -13, -12, -39, -21,
+    ],
+    [
+         32,  42,  32,  51,  63,   9,  31,  43,
+         27,  32,  58,  62,  80,  67,  26,  44,
+         -5,  19,  26,  36,  17,  45,  61,  16,
+        -24, -11,   7,  26,  24,  35,  -8, -20,
+        -36, -26, -12,  -1,   9,  -7,   6, -23,
+        -45, -25, -16, -17,   3,   0,  -5, -33,
+        -44, -16, -20,  -9,  -1,  11,  -6, -71,
+        -19, -13,   1,  17,  16,   7, -37, -26,
+    ],
+    [
+        -28,   0,  29,  12,  59,  44,  43,  45,
+        -24, -39,  -5,   1, -16,  57,  28,  54,
+        -13, -17,   7,   8,  29,  56,  47,  57,
+        -27, -27, -16, -16,  -1,  17,  -2,   1,
+         -9, -26,  -9, -10,  -2,  -4,   3,  -3,
+        -14,   2, -11,  -2,  -5,   2,  14,   5,
+        -35,  -8,  11,   2,   8,  15,  -3,   1,
+         -1, -18,  -9,  10, -15, -25, -31, -50,
+    ],
+    [
+        -65,  23,  16, -15, -56, -34,   2,  13,
+         29,  -1, -20,  -7,  -8,  -4, -38, -29,
+         -9,  24,   2, -16, -20,   6,  22, -22,
+        -17, -20, -12, -27, -30, -25, -14, -36,
+        -49,  -1, -27, -39, -46, -44, -33, -51,
+        -14, -14, -22, -46, -44, -30, -15, -27,
+          1,   7,  -8, -64, -43, -16,   9,   8,
+        -15,  36,  12, -54,   8, -28,  24,  14,
+    ],
+];
+
+#[rustfmt::skip]
+const EG_TABLES: [Table; 6] = [
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+        178, 173, 158, 134, 147, 132, 165, 187,
+         94, 100,  85,  67,  56,  53,  82,  84,
+         32,  24,  13,   5,  -2,   4,  17,  17,
+         13,   9,  -3,  -7,  -7,  -8,   3,  -1,
+          4,   7,  -6,   1,   0,  -5,  -1,  -8,
+         13,   8,   8,  10,  13,   0,   2,  -7,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    [
+        -58, -38, -13, -28, -31, -27, -63, -99,
+        -25,  -8, -25,  -2,  -9, -25, -24, -52,
+        -24, -20,  10,   9,  -1,  -9, -19, -41,
+        -17,   3,  22,  22,  22,  11,   8, -18,
+        -18,  -6,  16,  25,  16,  17,   4, -18,
+        -23,  -3,  -1,  15,  10,  -3, -20, -22,
+        -42, -20, -10,  -5,  -2, -20, -23, -44,
+        -29, -51, -23, -15, -22, -18, -50, -64,
+    ],
+    [
+        -14, -21, -11,  -8,  -7,  -9, -17, -24,
+         -8,  -4,   7, -12,  -3, -13,  -4, -14,
+          2,  -8,   0,  -1,  -2,   6,   0,   4,
+         -3,   9,  12,   9,  14,  10,   3,   2,
+         -6,   3,  13,  19,   7,  10,  -3,  -9,
+        -12,  -3,   8,  10,  13,   3,  -7, -15,
+        -14, -18,  -7,  -1,   4,  -9, -15, -27,
+        -23,  -9, -23,  -5,  -9, -16,  -5, -17,
+    ],
+    [
+         13,  10,  18,  15,  12,  12,   8,   5,
+         11,  13,  13,  11,  -3,   3,   8,   3,
+          7,   7,   7,   5,   4,  -3,  -5,  -3,
+          4,   3,  13,   1,   2,   1,  -1,   2,
+          3,   5,   8,   4,  -5,  -6,  -8, -11,
+         -4,   0,  -5,  -1,  -7, -12,  -8, -16,
+         -6,  -6,   0,   2,  -9,  -9, -11,  -3,
+         -9,   2,   3,  -1,  -5, -13,   4, -20,
+    ],
+    [
+         -9,  22,  22,  27,  27,  19,  10,  20,
+        -17,  20,  32,  41,  58,  25,  30,   0,
+        -20,   6,   9,  49,  47,  35,  19,   9,
+          3,  22,  24,  45,  57,  40,  57,  36,
+        -18,  28,  19,  47,  31,  34,  39,  23,
+        -16, -27,  15,   6,   9,  17,  10,   5,
+        -22, -23, -30, -16, -16, -23, -36, -32,
+        -33, -28, -22, -43,  -5, -32, -20, -41,
+    ],
+    [
+        -74, -35, -18, -18, -11,  15,   4, -17,
+        -12,  17,  14,  17,  17,  38,  23,  11,
+         10,  17,  23,  15,  20,  45,  44,  13,
+         -8,  22,  24,  27,  26,  33,  26,   3,
+        -18,  -4,  21,  24,  27,  23,   9, -11,
+        -19,  -3,  11,  21,  23,  16,   7,  -9,
+        -27, -11,   4,  13,  14,   4,  -5, -17,
+        -53, -34, -21, -11, -28, -14, -24, -43,
+    ],
+];
+
+// Material plus placement of every piece on every square, signed so white is positive.
+const fn generate_piece_square() -> [[Tapered; SQUARE_COUNT as usize]; PIECE_COUNT] {
+    let mut result = [[Tapered::ZERO; SQUARE_COUNT as usize]; PIECE_COUNT];
+    let mut piece = 0;
+
+    while piece < PIECE_COUNT {
+        let kind = piece % 6;
+        let white = piece < 6;
+        let mut square = 0;
+
+        while square < SQUARE_COUNT as usize {
+            // The tables start at a8, which is where black's pieces see their first rank.
+            let index = if white { square ^ 56 } else { square };
+            let mg = MG_VALUES[kind] + MG_TABLES[kind][index];
+            let eg = EG_VALUES[kind] + EG_TABLES[kind][index];
+
+            result[piece][square] = if white {
+                Tapered::new(mg, eg)
+            } else {
+                Tapered::new(-mg, -eg)
+            };
+            square += 1;
+        }
+
+        piece += 1;
+    }
+
+    result
+}
+
+static PIECE_SQUARE: [[Tapered; SQUARE_COUNT as usize]; PIECE_COUNT] = generate_piece_square();
+
+pub fn piece_square(piece: Piece, square: Square) -> Tapered {
+    PIECE_SQUARE[piece.index()][square as usize]
+}
+
+pub const fn piece_phase(piece: Piece) -> i32 {
+    PHASE_WEIGHTS[piece.index() % 6]
+}
+
+// Sum of piece_square over the board, Position keeps it up to date move by move.
+pub fn psqt(board: &Board) -> Tapered {
+    PIECE_SET
+        .iter()
+        .flat_map(|piece| {
+            bitboard_squares(board.piece_bit_board(*piece))
+                .map(|square| piece_square(*piece, square))
+        })
+        .fold(Tapered::ZERO, Add::add)
+}
+
+// Game phase from the non-pawn material left, MAX_PHASE at the start and 0 with bare kings.
+// Promotions can push it past MAX_PHASE.
+pub fn phase(board: &Board) -> i32 {
+    PIECE_SET
+        .iter()
+        .map(|piece| board.piece_bit_board(*piece).count_ones() as i32 * piece_phase(*piece))
+        .sum()
+}
+
+// Score in centipawns from the point of view of the side to move.
+pub fn evaluate(position: &Position) -> i32 {
+    blend(position, evaluate_pawns(position.board()))
+}
+
+// Same as evaluate, with the pawn structure looked up in the search's pawn table.
+pub fn evaluate_with(position: &Position, pawns: &mut PawnTable) -> i32 {
+    blend(position, pawns.probe(position.pawn_key(), position.board()))
+}
+
+fn blend(position: &Position, pawns: Tapered) -> i32 {
+    let score = (position.psqt() + pawns).blend(position.phase());
+
+    match position.side_to_move() {
+        Color::WHITE => score,
+        Color::BLACK => -score,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::DEFAULT_FEN;
+    use crate::types::square::Square::*;
+
+    fn evaluate_fen(fen: &str) -> i32 {
+        evaluate(&Position::from_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        let position = Position::from_fen(DEFAULT_FEN).unwrap();
+
+        assert_eq!(evaluate(&position), 0);
+        assert_eq!(position.phase(), MAX_PHASE);
+    }
+
+    #[test]
+    fn test_phase() {
+        let board = Position::from_fen("4k3/pppppppp/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let rooks = Position::from_fen("r3k3/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+
+        assert_eq!(phase(board.board()), 0);
+        assert_eq!(phase(rooks.board()), 6);
+    }
+
+    #[test]
+    fn test_evaluation_is_symmetric() {
+        // The same position with colors swapped and the board mirrored.
+        let white = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+        let black = "rnbqk2r/pppp1ppp/5n2/2b1p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R b KQkq - 4 4";
+
+        assert_eq!(evaluate_fen(white), evaluate_fen(black));
+        assert_ne!(evaluate_fen(white), 0);
+    }
+
+    #[test]
+    fn test_evaluation_is_relative_to_side_to_move() {
+        let white = evaluate_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 0 1");
+        let black = evaluate_fen("4k3/8/8/8/8/8/4P3/R3K3 b - - 0 1");
+
+        assert!(white > ROOK_VALUE);
+        assert_eq!(white, -black);
+    }
+
+    #[test]
+    fn test_tapering() {
+        // A centralised king is worth more once the queens are gone.
+        let middlegame =
+            piece_square(KING(Color::WHITE), E4) - piece_square(KING(Color::WHITE), G1);
+
+        assert!(middlegame.mg < 0);
+        assert!(middlegame.eg > 0);
+        assert_eq!(middlegame.blend(MAX_PHASE), middlegame.mg);
+        assert_eq!(middlegame.blend(0), middlegame.eg);
+        assert_eq!(middlegame.blend(MAX_PHASE + 4), middlegame.mg);
+    }
+
+    #[test]
+    fn test_piece_square_is_mirrored() {
+        assert_eq!(
+            piece_square(KNIGHT(Color::WHITE), F3),
+            -piece_square(KNIGHT(Color::BLACK), F6)
+        );
+        assert_eq!(
+            piece_square(PAWN(Color::WHITE), E7).eg,
+            EG_VALUES[0] + EG_TABLES[0][12]
+        );
+    }
+}
@@ -0,0 +1,449 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::{fill_board_fen, Board};
+use crate::constants::RANK_COUNT;
+use crate::piece::{Color, Piece::KING};
+use crate::position::{CastlingRights, Position};
+use crate::types::rank::Rank;
+use crate::types::square::Square;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    MissingField(&'static str),
+    TooManyFields,
+    InvalidRankCount(usize),
+    InvalidRankLength(Rank),
+    InvalidPiece(char),
+    SquareOccupied(Square),
+    InvalidKingCount(Color),
+    InvalidActiveColor(String),
+    InvalidCastlingRights(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "missing {} field", field),
+            FenError::TooManyFields => write!(f, "too many fields"),
+            FenError::InvalidRankCount(count) => write!(f, "expected 8 ranks, found {}", count),
+            FenError::InvalidRankLength(rank) => {
+                write!(f, "rank {} does not describe 8 squares", rank.to_char())
+            }
+            FenError::InvalidPiece(c) => write!(f, "invalid piece character '{}'", c),
+            FenError::SquareOccupied(square) => write!(f, "square {} is occupied twice", square),
+            FenError::InvalidKingCount(color) => {
+                write!(f, "{:?} must have exactly one king", color)
+            }
+            FenError::InvalidActiveColor(s) => write!(f, "invalid active color '{}'", s),
+            FenError::InvalidCastlingRights(s) => write!(f, "invalid castling rights '{}'", s),
+            FenError::InvalidEnPassant(s) => write!(f, "invalid en passant square '{}'", s),
+            FenError::InvalidHalfmoveClock(s) => write!(f, "invalid halfmove clock '{}'", s),
+            FenError::InvalidFullmoveNumber(s) => write!(f, "invalid fullmove number '{}'", s),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+fn parse_active_color(s: &str) -> Result<Color, FenError> {
+    match s {
+        "w" => Ok(Color::WHITE),
+        "b" => Ok(Color::BLACK),
+        _ => Err(FenError::InvalidActiveColor(s.to_string())),
+    }
+}
+
+fn parse_castling_rights(s: &str) -> Result<CastlingRights, FenError> {
+    let mut rights = CastlingRights::NONE;
+
+    if s == "-" {
+        return Ok(rights);
+    }
+
+    for c in s.chars() {
+        let right = match c {
+            'K' => CastlingRights::WHITE_KING_SIDE,
+            'Q' => CastlingRights::WHITE_QUEEN_SIDE,
+            'k' => CastlingRights::BLACK_KING_SIDE,
+            'q' => CastlingRights::BLACK_QUEEN_SIDE,
+            _ => return Err(FenError::InvalidCastlingRights(s.to_string())),
+        };
+
+        if rights.contains(right) {
+            return Err(FenError::InvalidCastlingRights(s.to_string()));
+        }
+        rights.insert(right);
+    }
+
+    if rights == CastlingRights::NONE {
+        Err(FenError::InvalidCastlingRights(s.to_string()))
+    } else {
+        Ok(rights)
+    }
+}
+
+fn castling_rights_to_fen(rights: CastlingRights) -> String {
+    let result: String = [
+        (CastlingRights::WHITE_KING_SIDE, 'K'),
+        (CastlingRights::WHITE_QUEEN_SIDE, 'Q'),
+        (CastlingRights::BLACK_KING_SIDE, 'k'),
+        (CastlingRights::BLACK_QUEEN_SIDE, 'q'),
+    ]
+    .iter()
+    .filter(|(right, _)| rights.contains(*right))
+    .map(|(_, c)| *c)
+    .collect();
+
+    if result.is_empty() {
+        "-".to_string()
+    } else {
+        result
+    }
+}
+
+fn parse_en_passant(s: &str, side_to_move: Color) -> Result<Option<Square>, FenError> {
+    if s == "-" {
+        return Ok(None);
+    }
+
+    let square: Square = s
+        .parse()
+        .map_err(|_| FenError::InvalidEnPassant(s.to_string()))?;
+
+    // The en passant square lies behind the pawn that just made a double push.
+    let expected_rank = match side_to_move {
+        Color::WHITE => Rank::SIX,
+        Color::BLACK => Rank::THREE,
+    };
+
+    if square.rank() == Some(expected_rank) {
+        Ok(Some(square))
+    } else {
+        Err(FenError::InvalidEnPassant(s.to_string()))
+    }
+}
+
+fn validate_kings(board: &Board) -> Result<(), FenError> {
+    for color in [Color::WHITE, Color::BLACK] {
+        if board.piece_bit_board(KING(color)).count_ones() != 1 {
+            return Err(FenError::InvalidKingCount(color));
+        }
+    }
+
+    Ok(())
+}
+
+impl Board {
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let mut board = Board::new();
+        fill_board_fen(&mut board, fen)?;
+
+        Ok(board)
+    }
+
+    pub fn to_fen(&self) -> String {
+        // Piece placement field only, runs of empty squares are compressed into a single digit.
+        let piecewise = self.piecewise_representation();
+        let mut result = String::new();
+
+        for (i, rank) in piecewise.iter().rev().enumerate() {
+            let mut empty = 0;
+
+            for piece_opt in rank {
+                match piece_opt {
+                    Some(piece) => {
+                        if empty > 0 {
+                            result.push(char::from_digit(empty, 10).unwrap());
+                            empty = 0;
+                        }
+                        result.push(piece.to_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                result.push(char::from_digit(empty, 10).unwrap());
+            }
+
+            if i + 1 < RANK_COUNT as usize {
+                result.push('/');
+            }
+        }
+
+        result
+    }
+}
+
+impl Position {
+    pub fn from_fen(fen: &str) -> Result<Position, FenError> {
+        // Halfmove clock and fullmove number are optional, as they are often missing in the wild.
+        let mut fields = fen.split_whitespace();
+
+        let placement = fields
+            .next()
+            .ok_or(FenError::MissingField("piece placement"))?;
+        let active_color = fields
+            .next()
+            .ok_or(FenError::MissingField("active color"))?;
+        let castling = fields
+            .next()
+            .ok_or(FenError::MissingField("castling rights"))?;
+        let en_passant = fields.next().ok_or(FenError::MissingField("en passant"))?;
+        let halfmove_clock = fields.next().unwrap_or("0");
+        let fullmove_number = fields.next().unwrap_or("1");
+
+        if fields.next().is_some() {
+            return Err(FenError::TooManyFields);
+        }
+
+        let board = Board::from_fen(placement)?;
+        validate_kings(&board)?;
+
+        let side_to_move = parse_active_color(active_color)?;
+        let castling_rights = parse_castling_rights(castling)?;
+        let en_passant = parse_en_passant(en_passant, side_to_move)?;
+
+        let halfmove_clock: u32 = halfmove_clock
+            .parse()
+            .map_err(|_| FenError::InvalidHalfmoveClock(halfmove_clock.to_string()))?;
+
+        let fullmove_number: u32 = fullmove_number
+            .parse()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or(FenError::InvalidFullmoveNumber(fullmove_number.to_string()))?;
+
+        Ok(Position::new(
+            board,
+            side_to_move,
+            castling_rights,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+        ))
+    }
+}
+
+impl Position {
+    pub fn to_fen(&self) -> String {
+        let active_color = match self.side_to_move() {
+            Color::WHITE => "w",
+            Color::BLACK => "b",
+        };
+
+        let en_passant = match self.en_passant() {
+            Some(square) => square.to_string(),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            self.board().to_fen(),
+            active_color,
+            castling_rights_to_fen(self.castling_rights()),
+            en_passant,
+            self.halfmove_clock(),
+            self.fullmove_number()
+        )
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_fen())
+    }
+}
+
+impl FromStr for Position {
+    type Err = FenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Position::from_fen(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::DEFAULT_FEN;
+    use crate::piece::Piece::*;
+    use crate::types::square::Square::*;
+
+    #[test]
+    fn test_from_fen_default() {
+        let position = Position::from_fen(DEFAULT_FEN).unwrap();
+
+        assert_eq!(position.side_to_move(), Color::WHITE);
+        assert_eq!(position.castling_rights(), CastlingRights::ALL);
+        assert_eq!(position.en_passant(), None);
+        assert_eq!(position.halfmove_clock(), 0);
+        assert_eq!(position.fullmove_number(), 1);
+        assert_eq!(
+            position.board().piece_on_square(E1),
+            Some(KING(Color::WHITE))
+        );
+        assert_eq!(
+            position.board().piece_on_square(D8),
+            Some(QUEEN(Color::BLACK))
+        );
+    }
+
+    #[test]
+    fn test_from_fen_all_fields() {
+        let position =
+            Position::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w Kq f6 0 3")
+                .unwrap();
+
+        assert_eq!(position.side_to_move(), Color::WHITE);
+        assert!(position
+            .castling_rights()
+            .contains(CastlingRights::WHITE_KING_SIDE));
+        assert!(!position
+            .castling_rights()
+            .contains(CastlingRights::WHITE_QUEEN_SIDE));
+        assert!(!position
+            .castling_rights()
+            .contains(CastlingRights::BLACK_KING_SIDE));
+        assert!(position
+            .castling_rights()
+            .contains(CastlingRights::BLACK_QUEEN_SIDE));
+        assert_eq!(position.en_passant(), Some(F6));
+        assert_eq!(position.fullmove_number(), 3);
+        assert_eq!(
+            position.board().piece_on_square(E5),
+            Some(PAWN(Color::WHITE))
+        );
+        assert_eq!(
+            position.board().piece_on_square(F5),
+            Some(PAWN(Color::BLACK))
+        );
+    }
+
+    #[test]
+    fn test_from_fen_without_clocks() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+
+        assert_eq!(position.side_to_move(), Color::BLACK);
+        assert_eq!(position.castling_rights(), CastlingRights::NONE);
+        assert_eq!(position.halfmove_clock(), 0);
+        assert_eq!(position.fullmove_number(), 1);
+    }
+
+    #[test]
+    fn test_from_fen_errors() {
+        assert_eq!(
+            Position::from_fen(""),
+            Err(FenError::MissingField("piece placement"))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4K3 w"),
+            Err(FenError::MissingField("castling rights"))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/4K3 w - - 0 1"),
+            Err(FenError::InvalidRankCount(7))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4K4 w - - 0 1"),
+            Err(FenError::InvalidRankLength(Rank::ONE))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4K2 w - - 0 1"),
+            Err(FenError::InvalidRankLength(Rank::ONE))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4X3 w - - 0 1"),
+            Err(FenError::InvalidPiece('X'))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/8 w - - 0 1"),
+            Err(FenError::InvalidKingCount(Color::WHITE))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            Err(FenError::InvalidActiveColor("x".to_string()))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4K3 w KK - 0 1"),
+            Err(FenError::InvalidCastlingRights("KK".to_string()))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - e3 0 1"),
+            Err(FenError::InvalidEnPassant("e3".to_string()))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - -1 1"),
+            Err(FenError::InvalidHalfmoveClock("-1".to_string()))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 0"),
+            Err(FenError::InvalidFullmoveNumber("0".to_string()))
+        );
+        assert_eq!(
+            Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1 extra"),
+            Err(FenError::TooManyFields)
+        );
+    }
+
+    const ROUND_TRIP_FENS: [&str; 6] = [
+        DEFAULT_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w Kq f6 0 3",
+        "4k3/8/8/8/4Pp2/8/8/4K3 b - e3 12 40",
+    ];
+
+    #[test]
+    fn test_board_to_fen() {
+        let board = Board::from_fen(DEFAULT_FEN).unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR"
+        );
+
+        let board = Board::from_fen("8/8/8/8/8/8/8/8").unwrap();
+        assert_eq!(board.to_fen(), "8/8/8/8/8/8/8/8");
+
+        let board = Board::from_fen("k7/8/44/8/8/8/1111P3/7K").unwrap();
+        assert_eq!(board.to_fen(), "k7/8/8/8/8/8/4P3/7K");
+    }
+
+    #[test]
+    fn test_board_round_trip() {
+        for fen in ROUND_TRIP_FENS {
+            let board = Board::from_fen(fen).unwrap();
+
+            assert_eq!(Board::from_fen(&board.to_fen()).unwrap(), board);
+        }
+    }
+
+    #[test]
+    fn test_position_round_trip() {
+        for fen in ROUND_TRIP_FENS {
+            let position = Position::from_fen(fen).unwrap();
+
+            assert_eq!(position.to_fen(), fen);
+            assert_eq!(Position::from_fen(&position.to_fen()).unwrap(), position);
+        }
+    }
+
+    #[test]
+    fn test_position_to_fen_fills_missing_clocks() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+
+        assert_eq!(position.to_string(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn test_from_str() {
+        let position: Position = DEFAULT_FEN.parse().unwrap();
+
+        assert_eq!(position, Position::from_fen(DEFAULT_FEN).unwrap());
+    }
+}
@@ -0,0 +1,21 @@
+#![allow(clippy::upper_case_acronyms)]
+
+pub mod attack_maps;
+pub mod board;
+pub mod constants;
+pub mod eval;
+pub mod fen;
+pub mod movegen;
+pub mod movepick;
+pub mod moves;
+pub mod pawns;
+pub mod perft;
+pub mod piece;
+pub mod polyglot;
+pub mod position;
+pub mod search;
+pub mod see;
+pub mod tt;
+pub mod types;
+pub mod uci;
+pub mod zobrist;
@@ -1,14 +1,49 @@
-mod attack_maps;
-mod board;
-mod constants;
-mod piece;
-mod types;
+use std::env;
+use std::io;
+use std::process;
 
-use crate::board::{fill_board_fen, print_board, Board};
-use crate::constants::DEFAULT_FEN;
+use ruch::position::Position;
+use ruch::uci::Uci;
+
+fn perft(args: &[String]) -> Result<(), String> {
+    // The FEN may be passed quoted as one argument or unquoted as several.
+    let (depth, fen) = match args.split_last() {
+        Some((depth, fen)) if !fen.is_empty() => (depth, fen.join(" ")),
+        _ => return Err("usage: ruch perft <fen> <depth>".to_string()),
+    };
+
+    let depth: u32 = depth
+        .parse()
+        .ok()
+        .filter(|d| *d > 0)
+        .ok_or(format!("invalid depth '{}'", depth))?;
+    let mut position = Position::from_fen(&fen).map_err(|e| format!("invalid FEN: {}", e))?;
+
+    let mut total = 0;
+    for (mv, nodes) in position.divide(depth) {
+        println!("{}: {}", mv, nodes);
+        total += nodes;
+    }
+
+    println!();
+    println!("Nodes searched: {}", total);
+
+    Ok(())
+}
 
 fn main() {
-    let mut board = Board::new();
-    fill_board_fen(&mut board, DEFAULT_FEN);
-    print_board(&board);
+    let args: Vec<String> = env::args().collect();
+
+    let result = match args.get(1).map(String::as_str) {
+        Some("perft") => perft(&args[2..]),
+        _ => {
+            Uci::new(io::stdout()).run(io::stdin().lock());
+            Ok(())
+        }
+    };
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
 }
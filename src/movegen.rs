@@ -0,0 +1,621 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::attack_maps::ray_attacks::{between, line};
+use crate::attack_maps::sliders::{bishop_attacks, rook_attacks};
+use crate::board::Board;
+use crate::constants::{EIGHT_RANK, FULL_BOARD, ONE_RANK, ZERO};
+use crate::moves::Move;
+use crate::piece::{Color, Piece::*};
+use crate::position::{en_passant_capture_square, CastlingRights, Position};
+use crate::types::bitboard::{bitboard_squares, BitBoard};
+use crate::types::square::{Square, Square::*};
+
+pub const MAX_MOVES: usize = 256;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        MoveList::new()
+    }
+}
+
+impl MoveList {
+    pub fn new() -> MoveList {
+        MoveList {
+            moves: [Move::NULL; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: Move) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(Move) -> bool,
+    {
+        let mut kept = 0;
+
+        for i in 0..self.len {
+            if keep(self.moves[i]) {
+                self.moves[kept] = self.moves[i];
+                kept += 1;
+            }
+        }
+
+        self.len = kept;
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GenMode {
+    ALL,
+    // Captures, en passant and promotions, the moves a quiescence search looks at.
+    CAPTURES,
+    // Everything CAPTURES leaves out.
+    QUIETS,
+}
+
+pub fn is_square_attacked(board: &Board, square: Square, by: Color) -> bool {
+    board.attackers_by(square, by, board.all_bit_boards()) != ZERO
+}
+
+fn push_moves(list: &mut MoveList, from: Square, targets: BitBoard, enemies: BitBoard) {
+    for to in bitboard_squares(targets) {
+        let flags = if enemies & to.as_bitboard() != ZERO {
+            Move::CAPTURE
+        } else {
+            Move::QUIET
+        };
+
+        list.push(Move::new(from, to, flags));
+    }
+}
+
+fn push_pawn_moves(list: &mut MoveList, from: Square, to: Square, flags: u16) {
+    if to.as_bitboard() & (ONE_RANK | EIGHT_RANK) != ZERO {
+        for promotion in [
+            Move::QUEEN_PROMOTION,
+            Move::ROOK_PROMOTION,
+            Move::BISHOP_PROMOTION,
+            Move::KNIGHT_PROMOTION,
+        ] {
+            list.push(Move::new(from, to, promotion | flags));
+        }
+    } else {
+        list.push(Move::new(from, to, flags));
+    }
+}
+
+// Only pieces standing on origins are looked at.
+fn generate_pawn_moves(position: &Position, list: &mut MoveList, mode: GenMode, origins: BitBoard) {
+    let board = position.board();
+    let color = position.side_to_move();
+    let occupancy = board.all_bit_boards();
+    let pawn = PAWN(color);
+    let pawns = board.piece_bit_board(pawn) & origins;
+    let (push_targets, enemies) = match mode {
+        GenMode::ALL => (FULL_BOARD, board.color_bit_board(color.oposite())),
+        GenMode::CAPTURES => (
+            ONE_RANK | EIGHT_RANK,
+            board.color_bit_board(color.oposite()),
+        ),
+        GenMode::QUIETS => (!(ONE_RANK | EIGHT_RANK), ZERO),
+    };
+
+    for from in bitboard_squares(pawns) {
+        for to in bitboard_squares(pawn.push(from, occupancy) & push_targets) {
+            if (to as u64).abs_diff(from as u64) == 16 {
+                list.push(Move::new(from, to, Move::DOUBLE_PAWN_PUSH));
+            } else {
+                push_pawn_moves(list, from, to, Move::QUIET);
+            }
+        }
+
+        for to in bitboard_squares(pawn.attacks(from, occupancy) & enemies) {
+            push_pawn_moves(list, from, to, Move::CAPTURE);
+        }
+    }
+
+    if let Some(en_passant) = position.en_passant().filter(|_| mode != GenMode::QUIETS) {
+        let attackers = PAWN(color.oposite()).attacks(en_passant, occupancy) & pawns;
+
+        for from in bitboard_squares(attackers) {
+            list.push(Move::new(from, en_passant, Move::EN_PASSANT));
+        }
+    }
+}
+
+fn generate_piece_moves(
+    position: &Position,
+    list: &mut MoveList,
+    mode: GenMode,
+    origins: BitBoard,
+) {
+    let board = position.board();
+    let color = position.side_to_move();
+    let occupancy = board.all_bit_boards();
+    let enemies = board.color_bit_board(color.oposite());
+    let targets = match mode {
+        GenMode::ALL => !board.color_bit_board(color),
+        GenMode::CAPTURES => enemies,
+        GenMode::QUIETS => !occupancy,
+    };
+
+    for piece in [
+        KNIGHT(color),
+        BISHOP(color),
+        ROOK(color),
+        QUEEN(color),
+        KING(color),
+    ] {
+        for from in bitboard_squares(board.piece_bit_board(piece) & origins) {
+            push_moves(
+                list,
+                from,
+                piece.attacks(from, occupancy) & targets,
+                enemies,
+            );
+        }
+    }
+}
+
+fn generate_castling_moves(position: &Position, list: &mut MoveList) {
+    // The king may not castle out of, through or into check.
+    let board = position.board();
+    let color = position.side_to_move();
+    let rights = position.castling_rights();
+
+    let (king_side, queen_side, king, rank_offset) = match color {
+        Color::WHITE => (
+            CastlingRights::WHITE_KING_SIDE,
+            CastlingRights::WHITE_QUEEN_SIDE,
+            E1,
+            0,
+        ),
+        Color::BLACK => (
+            CastlingRights::BLACK_KING_SIDE,
+            CastlingRights::BLACK_QUEEN_SIDE,
+            E8,
+            56,
+        ),
+    };
+
+    let square = |square: Square| Square::try_from(square as u64 + rank_offset).unwrap();
+    let empty = |squares: &[Square]| {
+        squares
+            .iter()
+            .all(|s| board.piece_on_square(square(*s)).is_none())
+    };
+    let safe = |squares: &[Square]| {
+        squares
+            .iter()
+            .all(|s| !is_square_attacked(board, square(*s), color.oposite()))
+    };
+
+    if board.piece_on_square(king) != Some(KING(color)) {
+        return;
+    }
+
+    if rights.contains(king_side)
+        && board.piece_on_square(square(H1)) == Some(ROOK(color))
+        && empty(&[F1, G1])
+        && safe(&[E1, F1, G1])
+    {
+        list.push(Move::new(king, square(G1), Move::KING_CASTLE));
+    }
+
+    if rights.contains(queen_side)
+        && board.piece_on_square(square(A1)) == Some(ROOK(color))
+        && empty(&[B1, C1, D1])
+        && safe(&[E1, D1, C1])
+    {
+        list.push(Move::new(king, square(C1), Move::QUEEN_CASTLE));
+    }
+}
+
+pub fn generate_pseudo_legal(position: &Position, list: &mut MoveList, mode: GenMode) {
+    generate_pawn_moves(position, list, mode, FULL_BOARD);
+    generate_piece_moves(position, list, mode, FULL_BOARD);
+
+    if mode != GenMode::CAPTURES {
+        generate_castling_moves(position, list);
+    }
+}
+
+pub fn is_pseudo_legal(position: &Position, mv: Move) -> bool {
+    // Moves from elsewhere, a hash table or a killer slot, are checked by generating the moves
+    // of the piece on their origin square.
+    let mut list = MoveList::new();
+    let origin = mv.from().as_bitboard();
+
+    if mv.is_castle() {
+        generate_castling_moves(position, &mut list);
+    } else {
+        generate_pawn_moves(position, &mut list, GenMode::ALL, origin);
+        generate_piece_moves(position, &mut list, GenMode::ALL, origin);
+    }
+
+    list.contains(&mv)
+}
+
+pub fn generate_pseudo_legal_moves(position: &Position, list: &mut MoveList) {
+    generate_pseudo_legal(position, list, GenMode::ALL);
+}
+
+pub fn is_legal(position: &Position, mv: Move) -> bool {
+    // Plays the move on a copy of the board and checks whether the own king is left attacked.
+    // Castling moves are already checked for attacked squares during generation.
+    if mv.is_castle() {
+        return true;
+    }
+
+    let color = position.side_to_move();
+    let mut board = *position.board();
+
+    let piece = match board.remove_piece_from_square(mv.from()) {
+        Some(piece) => piece,
+        None => return false,
+    };
+
+    if mv.is_en_passant() {
+        board.remove_piece_from_square(en_passant_capture_square(mv));
+    } else if mv.is_capture() {
+        board.remove_piece_from_square(mv.to());
+    }
+
+    if board.put_piece_on_square(piece, mv.to()).is_err() {
+        return false;
+    }
+
+    match bitboard_squares(board.piece_bit_board(KING(color))).next() {
+        Some(king) => !is_square_attacked(&board, king, color.oposite()),
+        None => false,
+    }
+}
+
+pub fn generate_legal(position: &Position, list: &mut MoveList, mode: GenMode) {
+    generate_pseudo_legal(position, list, mode);
+    list.retain(|mv| is_legal(position, mv));
+}
+
+pub fn generate_legal_moves(position: &Position, list: &mut MoveList) {
+    generate_legal(position, list, GenMode::ALL);
+}
+
+impl Position {
+    pub fn legal_moves(&self) -> MoveList {
+        let mut list = MoveList::new();
+        generate_legal_moves(self, &mut list);
+
+        list
+    }
+
+    pub fn legal_captures(&self) -> MoveList {
+        let mut list = MoveList::new();
+        generate_legal(self, &mut list, GenMode::CAPTURES);
+
+        list
+    }
+
+    pub fn in_check(&self) -> bool {
+        let color = self.side_to_move();
+
+        bitboard_squares(self.board().piece_bit_board(KING(color)))
+            .any(|king| is_square_attacked(self.board(), king, color.oposite()))
+    }
+
+    fn king_square(&self) -> Option<Square> {
+        bitboard_squares(self.board().piece_bit_board(KING(self.side_to_move()))).next()
+    }
+
+    pub fn checkers(&self) -> BitBoard {
+        // Enemy pieces attacking the king of the side to move.
+        let board = self.board();
+
+        match self.king_square() {
+            Some(king) => {
+                board.attackers_by(king, self.side_to_move().oposite(), board.all_bit_boards())
+            }
+            None => ZERO,
+        }
+    }
+
+    pub fn pinned(&self) -> BitBoard {
+        // Friendly pieces that are the only piece between the king and an enemy slider.
+        let board = self.board();
+        let color = self.side_to_move();
+        let enemy = color.oposite();
+        let occupancy = board.all_bit_boards();
+
+        let king = match self.king_square() {
+            Some(king) => king,
+            None => return ZERO,
+        };
+
+        let queens = board.piece_bit_board(QUEEN(enemy));
+        let snipers = bishop_attacks(king, ZERO) & (board.piece_bit_board(BISHOP(enemy)) | queens)
+            | rook_attacks(king, ZERO) & (board.piece_bit_board(ROOK(enemy)) | queens);
+
+        bitboard_squares(snipers).fold(ZERO, |pinned, sniper| {
+            let blockers = between(king, sniper) & occupancy;
+
+            if blockers.count_ones() == 1 && blockers & board.color_bit_board(color) != ZERO {
+                pinned | blockers
+            } else {
+                pinned
+            }
+        })
+    }
+
+    pub fn pin_ray(&self, square: Square) -> BitBoard {
+        // Squares a piece on `square` may move to without exposing its king, everything if it is not pinned.
+        match self.king_square() {
+            Some(king) if self.pinned() & square.as_bitboard() != ZERO => line(king, square),
+            _ => FULL_BOARD,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::DEFAULT_FEN;
+
+    fn legal_moves(fen: &str) -> MoveList {
+        Position::from_fen(fen).unwrap().legal_moves()
+    }
+
+    fn contains(list: &MoveList, uci: &str) -> bool {
+        list.iter().any(|mv| mv.to_uci() == uci)
+    }
+
+    #[test]
+    fn test_start_position() {
+        let moves = legal_moves(DEFAULT_FEN);
+
+        assert_eq!(moves.len(), 20);
+        assert!(contains(&moves, "e2e4"));
+        assert!(contains(&moves, "g1f3"));
+        assert!(!contains(&moves, "e1e2"));
+    }
+
+    #[test]
+    fn test_kiwipete() {
+        let moves =
+            legal_moves("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+
+        assert_eq!(moves.len(), 48);
+        assert!(contains(&moves, "e1g1"));
+        assert!(contains(&moves, "e1c1"));
+    }
+
+    #[test]
+    fn test_en_passant() {
+        let moves = legal_moves("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+
+        assert!(contains(&moves, "e5f6"));
+        assert!(!contains(&moves, "e5d6"));
+        assert!(moves
+            .iter()
+            .any(|mv| mv.to_uci() == "e5f6" && mv.is_en_passant()));
+    }
+
+    #[test]
+    fn test_en_passant_exposing_king_is_illegal() {
+        let moves = legal_moves("8/8/8/K2pP2r/8/8/8/7k w - d6 0 1");
+
+        assert!(!contains(&moves, "e5d6"));
+    }
+
+    #[test]
+    fn test_promotions() {
+        let moves = legal_moves("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+
+        for uci in ["a7a8q", "a7a8r", "a7a8b", "a7a8n", "a7b8q", "a7b8n"] {
+            assert!(contains(&moves, uci), "{} missing", uci);
+        }
+    }
+
+    #[test]
+    fn test_captures_only() {
+        let position = Position::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let captures = position.legal_captures();
+
+        assert_eq!(captures.len(), 8);
+        assert!(captures.iter().all(|mv| mv.is_capture()));
+
+        let all = position.legal_moves();
+        let expected = all.iter().filter(|mv| mv.is_capture()).count();
+        assert_eq!(captures.len(), expected);
+
+        // Quiet promotions count, pushes and castling do not.
+        let captures = Position::from_fen("1r2k3/P7/8/3pP3/8/8/8/R3K3 w Q d6 0 1")
+            .unwrap()
+            .legal_captures();
+        let mut uci: Vec<String> = captures.iter().map(|mv| mv.to_uci()).collect();
+        uci.sort();
+
+        assert_eq!(
+            uci,
+            vec!["a7a8b", "a7a8n", "a7a8q", "a7a8r", "a7b8b", "a7b8n", "a7b8q", "a7b8r", "e5d6"]
+        );
+    }
+
+    #[test]
+    fn test_captures_and_quiets_split_all_moves() {
+        for fen in [
+            DEFAULT_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "1r2k3/P7/8/3pP3/8/8/8/R3K3 w Q d6 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        ] {
+            let position = Position::from_fen(fen).unwrap();
+            let (mut all, mut split) = (MoveList::new(), MoveList::new());
+
+            generate_pseudo_legal(&position, &mut all, GenMode::ALL);
+            generate_pseudo_legal(&position, &mut split, GenMode::CAPTURES);
+            generate_pseudo_legal(&position, &mut split, GenMode::QUIETS);
+
+            let mut all = all.to_vec();
+            let mut split = split.to_vec();
+            all.sort_by_key(|mv| mv.bits());
+            split.sort_by_key(|mv| mv.bits());
+
+            assert_eq!(all, split, "{}", fen);
+            assert!(all.iter().all(|mv| is_pseudo_legal(&position, *mv)));
+        }
+    }
+
+    #[test]
+    fn test_is_pseudo_legal() {
+        let position = Position::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+
+        assert!(is_pseudo_legal(&position, Move::new(E5, F7, Move::CAPTURE)));
+        assert!(is_pseudo_legal(
+            &position,
+            Move::new(E1, G1, Move::KING_CASTLE)
+        ));
+        // Wrong flags, a blocked slider, an empty origin and an enemy piece.
+        assert!(!is_pseudo_legal(&position, Move::new(E5, F7, Move::QUIET)));
+        assert!(!is_pseudo_legal(&position, Move::new(A1, A3, Move::QUIET)));
+        assert!(!is_pseudo_legal(&position, Move::new(E3, E4, Move::QUIET)));
+        assert!(!is_pseudo_legal(&position, Move::new(A6, B5, Move::QUIET)));
+        assert!(!is_pseudo_legal(&position, Move::NULL));
+    }
+
+    #[test]
+    fn test_castling_through_check_is_illegal() {
+        let moves = legal_moves("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1");
+        assert!(!contains(&moves, "e1g1"));
+        assert!(contains(&moves, "e1c1"));
+
+        let moves = legal_moves("r3k2r/8/8/8/4r3/8/8/R3K2R w KQkq - 0 1");
+        assert!(!contains(&moves, "e1g1"));
+        assert!(!contains(&moves, "e1c1"));
+
+        let moves = legal_moves("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert!(contains(&moves, "e1g1"));
+        assert!(contains(&moves, "e1c1"));
+
+        // b1 may be attacked on the queen side, only the squares the king crosses matter.
+        let moves = legal_moves("1r2k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1");
+        assert!(contains(&moves, "e1c1"));
+    }
+
+    #[test]
+    fn test_pinned_piece_cannot_leave_pin_ray() {
+        let moves = legal_moves("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1");
+
+        assert!(moves.iter().all(|mv| mv.from() != E2));
+    }
+
+    #[test]
+    fn test_check_evasions() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/3q4/4K3 w - - 0 1").unwrap();
+
+        assert!(position.in_check());
+        assert_eq!(position.legal_moves().len(), 2);
+        assert!(contains(&position.legal_moves(), "e1d2"));
+        assert!(contains(&position.legal_moves(), "e1f1"));
+    }
+
+    #[test]
+    fn test_checkmate_has_no_moves() {
+        let position =
+            Position::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+
+        assert!(position.in_check());
+        assert_eq!(position.legal_moves().len(), 0);
+    }
+
+    #[test]
+    fn test_checkers() {
+        let position = Position::from_fen(DEFAULT_FEN).unwrap();
+        assert_eq!(position.checkers(), ZERO);
+
+        // Double check by the rook on e8 and the knight on d3.
+        let position = Position::from_fen("4r2k/8/8/8/8/3n4/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(position.checkers(), E8.as_bitboard() | D3.as_bitboard());
+        assert!(position.in_check());
+
+        let position = Position::from_fen("7k/8/8/8/8/8/3p4/4K3 w - - 0 1").unwrap();
+        assert_eq!(position.checkers(), D2.as_bitboard());
+
+        let position = Position::from_fen("7k/8/8/8/8/8/4p3/4K3 w - - 0 1").unwrap();
+        assert_eq!(position.checkers(), ZERO);
+    }
+
+    #[test]
+    fn test_pinned_and_pin_ray() {
+        // The bishop on e2 is pinned by the rook, the knight on c3 by the queen. The pawn on
+        // f2 is shielded by the g3 pawn from the bishop on h4, which therefore pins nothing.
+        let position = Position::from_fen("4r2k/8/8/q7/7b/2N3P1/4BP2/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(position.pinned(), E2.as_bitboard() | C3.as_bitboard());
+        assert_eq!(position.pin_ray(E2), line(E1, E8));
+        assert_eq!(position.pin_ray(C3), line(E1, A5));
+        assert_eq!(position.pin_ray(F2), FULL_BOARD);
+
+        // Enemy pieces in between do not count as pinned.
+        let position = Position::from_fen("4r2k/8/8/8/8/8/4n3/4K3 w - - 0 1").unwrap();
+        assert_eq!(position.pinned(), ZERO);
+    }
+
+    #[test]
+    fn test_pinned_pieces_only_move_along_their_pin_ray() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4r2k/8/8/q7/7b/2N3P1/4BP2/4K3 w - - 0 1",
+        ] {
+            let position = Position::from_fen(fen).unwrap();
+            let mut list = MoveList::new();
+            generate_pseudo_legal_moves(&position, &mut list);
+
+            for mv in list.iter() {
+                if position.checkers() == ZERO && !mv.is_en_passant() && !mv.is_castle() {
+                    let along_pin_ray = position.pin_ray(mv.from()) & mv.to().as_bitboard() != ZERO;
+                    let king_move = Some(mv.from()) == position.king_square();
+
+                    if !king_move {
+                        assert_eq!(is_legal(&position, *mv), along_pin_ray, "{} in {}", mv, fen);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use crate::constants::SQUARE_COUNT;
+use crate::movegen::{generate_pseudo_legal, is_pseudo_legal, GenMode, MoveList, MAX_MOVES};
+use crate::moves::Move;
+use crate::piece::{Color, Piece, Piece::*, COLOR_COUNT, PIECE_COUNT};
+use crate::position::Position;
+use crate::search::MAX_PLY;
+use crate::types::square::Square;
+
+// History scores stay within this bound, old entries fading as new ones come in.
+const MAX_HISTORY: i32 = 16_384;
+
+pub fn is_quiet(mv: Move) -> bool {
+    !mv.is_capture() && !mv.is_promotion()
+}
+
+// Piece kind from pawn = 0 to king = 5.
+fn ordering_value(piece: Piece) -> i32 {
+    (piece.index() % 6) as i32
+}
+
+fn captured(position: &Position, mv: Move) -> Option<Piece> {
+    if mv.is_en_passant() {
+        Some(PAWN(position.side_to_move().oposite()))
+    } else {
+        position.board().piece_on_square(mv.to())
+    }
+}
+
+// Most valuable victim first, among equal victims the least valuable attacker.
+pub fn mvv_lva(position: &Position, mv: Move) -> i32 {
+    let attacker = position
+        .board()
+        .piece_on_square(mv.from())
+        .map_or(0, ordering_value);
+    let victim = captured(position, mv).map_or(0, |piece| 8 * ordering_value(piece));
+    let promotion = mv
+        .promotion(position.side_to_move())
+        .map_or(0, |piece| 8 * ordering_value(piece));
+
+    victim + promotion + 6 - attacker
+}
+
+pub struct Heuristics {
+    // Two quiet moves per ply that caused a cutoff in a sibling node.
+    killers: [[Move; 2]; MAX_PLY],
+    // Butterfly history: cutoffs caused by a quiet move, indexed by side, origin and target.
+    history: [[[i32; SQUARE_COUNT as usize]; SQUARE_COUNT as usize]; COLOR_COUNT],
+    // The quiet move that refuted a move, indexed by the piece that moved and where it went.
+    counter_moves: [[Move; SQUARE_COUNT as usize]; PIECE_COUNT],
+}
+
+impl Heuristics {
+    pub fn new() -> Box<Heuristics> {
+        Box::new(Heuristics {
+            killers: [[Move::NULL; 2]; MAX_PLY],
+            history: [[[0; SQUARE_COUNT as usize]; SQUARE_COUNT as usize]; COLOR_COUNT],
+            counter_moves: [[Move::NULL; SQUARE_COUNT as usize]; PIECE_COUNT],
+        })
+    }
+
+    pub fn killers(&self, ply: usize) -> [Move; 2] {
+        self.killers[ply]
+    }
+
+    pub fn history(&self, color: Color, mv: Move) -> i32 {
+        self.history[color.index()][mv.from() as usize][mv.to() as usize]
+    }
+
+    fn last_move(position: &Position) -> Option<(Piece, Square)> {
+        let mv = position.history().last()?.mv();
+        let piece = position
+            .board()
+            .piece_on_square(mv.to())
+            .filter(|_| !mv.is_null())?;
+
+        Some((piece, mv.to()))
+    }
+
+    pub fn counter_move(&self, position: &Position) -> Move {
+        match Heuristics::last_move(position) {
+            Some((piece, to)) => self.counter_moves[piece.index()][to as usize],
+            None => Move::NULL,
+        }
+    }
+
+    fn add_history(&mut self, color: Color, mv: Move, bonus: i32) {
+        let entry = &mut self.history[color.index()][mv.from() as usize][mv.to() as usize];
+        *entry += bonus - *entry * bonus.abs() / MAX_HISTORY;
+    }
+
+    // Rewards the quiet move that caused a cutoff and punishes the quiet ones tried before it.
+    pub fn update(
+        &mut self,
+        position: &Position,
+        best: Move,
+        depth: i32,
+        ply: usize,
+        tried: &[Move],
+    ) {
+        let color = position.side_to_move();
+        let bonus = (depth * depth).min(MAX_HISTORY / 8);
+
+        self.add_history(color, best, bonus);
+        for mv in tried.iter().filter(|mv| **mv != best) {
+            self.add_history(color, *mv, -bonus);
+        }
+
+        if self.killers[ply][0] != best {
+            self.killers[ply][1] = self.killers[ply][0];
+            self.killers[ply][0] = best;
+        }
+
+        if let Some((piece, to)) = Heuristics::last_move(position) {
+            self.counter_moves[piece.index()][to as usize] = best;
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Stage {
+    HASH,
+    INITCAPTURES,
+    GOODCAPTURES,
+    KILLERS,
+    COUNTER,
+    INITQUIETS,
+    QUIETS,
+    BADCAPTURES,
+    DONE,
+}
+
+// Hands out pseudo-legal moves best first, generating each group only once it is reached so
+// that a cutoff early on saves the work for the rest.
+pub struct MovePicker {
+    stage: Stage,
+    captures_only: bool,
+    hash_move: Move,
+    killers: [Move; 2],
+    counter_move: Move,
+    refutations_tried: usize,
+    moves: MoveList,
+    scores: [i32; MAX_MOVES],
+    current: usize,
+    bad_captures: MoveList,
+    bad_current: usize,
+}
+
+impl MovePicker {
+    pub fn new(hash_move: Move, killers: [Move; 2], counter_move: Move) -> MovePicker {
+        MovePicker {
+            stage: Stage::HASH,
+            captures_only: false,
+            hash_move,
+            killers,
+            counter_move,
+            refutations_tried: 0,
+            moves: MoveList::new(),
+            scores: [0; MAX_MOVES],
+            current: 0,
+            bad_captures: MoveList::new(),
+            bad_current: 0,
+        }
+    }
+
+    // Captures and promotions by MVV-LVA, nothing else.
+    pub fn captures() -> MovePicker {
+        MovePicker {
+            stage: Stage::INITCAPTURES,
+            captures_only: true,
+            ..MovePicker::new(Move::NULL, [Move::NULL; 2], Move::NULL)
+        }
+    }
+
+    fn generate<F>(&mut self, position: &Position, mode: GenMode, score: F)
+    where
+        F: Fn(Move) -> i32,
+    {
+        self.moves.clear();
+        self.current = 0;
+        generate_pseudo_legal(position, &mut self.moves, mode);
+
+        for (i, mv) in self.moves.iter().enumerate() {
+            self.scores[i] = score(*mv);
+        }
+    }
+
+    // Selection sort one step at a time, most moves are never looked at.
+    fn pick_best(&mut self) -> Option<Move> {
+        let best = (self.current..self.moves.len()).max_by_key(|i| self.scores[*i])?;
+
+        self.moves.swap(self.current, best);
+        self.scores.swap(self.current, best);
+        self.current += 1;
+
+        Some(self.moves[self.current - 1])
+    }
+
+    fn is_refutation(&self, mv: Move) -> bool {
+        self.killers.contains(&mv) || mv == self.counter_move
+    }
+
+    fn usable(&self, position: &Position, mv: Move) -> bool {
+        !mv.is_null() && mv != self.hash_move && is_quiet(mv) && is_pseudo_legal(position, mv)
+    }
+
+    pub fn next(&mut self, position: &Position, heuristics: &Heuristics) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::HASH => {
+                    self.stage = Stage::INITCAPTURES;
+
+                    if !self.hash_move.is_null() && is_pseudo_legal(position, self.hash_move) {
+                        return Some(self.hash_move);
+                    }
+                }
+                Stage::INITCAPTURES => {
+                    self.generate(position, GenMode::CAPTURES, |mv| mvv_lva(position, mv));
+                    self.stage = Stage::GOODCAPTURES;
+                }
+                Stage::GOODCAPTURES => match self.pick_best() {
+                    Some(mv) if mv == self.hash_move => {}
+                    Some(mv) if !self.captures_only && !position.see_ge(mv, 0) => {
+                        self.bad_captures.push(mv)
+                    }
+                    Some(mv) => return Some(mv),
+                    None if self.captures_only => self.stage = Stage::DONE,
+                    None => self.stage = Stage::KILLERS,
+                },
+                Stage::KILLERS => {
+                    while self.refutations_tried < self.killers.len() {
+                        let killer = self.killers[self.refutations_tried];
+                        self.refutations_tried += 1;
+
+                        if self.usable(position, killer) {
+                            return Some(killer);
+                        }
+                    }
+
+                    self.stage = Stage::COUNTER;
+                }
+                Stage::COUNTER => {
+                    self.stage = Stage::INITQUIETS;
+                    let counter = self.counter_move;
+
+                    if !self.killers.contains(&counter) && self.usable(position, counter) {
+                        return Some(counter);
+                    }
+                }
+                Stage::INITQUIETS => {
+                    let color = position.side_to_move();
+                    self.generate(position, GenMode::QUIETS, |mv| {
+                        heuristics.history(color, mv)
+                    });
+                    self.stage = Stage::QUIETS;
+                }
+                Stage::QUIETS => match self.pick_best() {
+                    Some(mv) if mv == self.hash_move || self.is_refutation(mv) => {}
+                    Some(mv) => return Some(mv),
+                    None => self.stage = Stage::BADCAPTURES,
+                },
+                Stage::BADCAPTURES => {
+                    if let Some(mv) = self.bad_captures.get(self.bad_current).copied() {
+                        self.bad_current += 1;
+                        return Some(mv);
+                    }
+
+                    self.stage = Stage::DONE;
+                }
+                Stage::DONE => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::movegen::generate_pseudo_legal_moves;
+    use crate::types::square::Square::*;
+
+    fn picked(position: &Position, mut picker: MovePicker, heuristics: &Heuristics) -> Vec<Move> {
+        std::iter::from_fn(|| picker.next(position, heuristics)).collect()
+    }
+
+    #[test]
+    fn test_every_move_once() {
+        let heuristics = Heuristics::new();
+
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            let position = Position::from_fen(fen).unwrap();
+            let mut expected = MoveList::new();
+            generate_pseudo_legal_moves(&position, &mut expected);
+
+            // A hash move and refutations which may or may not be legal here.
+            let picker = MovePicker::new(
+                expected[expected.len() / 2],
+                [
+                    Move::new(A2, A3, Move::QUIET),
+                    Move::new(H7, H6, Move::QUIET),
+                ],
+                Move::new(E1, G1, Move::KING_CASTLE),
+            );
+            let mut moves = picked(&position, picker, &heuristics);
+
+            let mut expected = expected.to_vec();
+            moves.sort_by_key(|mv| mv.bits());
+            expected.sort_by_key(|mv| mv.bits());
+            assert_eq!(moves, expected, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_stage_order() {
+        // Pawn takes queen, rook takes defended pawn, and plenty of quiet moves.
+        let position = Position::from_fen("4k3/8/2p5/1p1q4/4P3/8/8/1R2K2R w K - 0 1").unwrap();
+        let mut heuristics = Heuristics::new();
+        let hash_move = Move::new(H1, H7, Move::QUIET);
+        let killer = Move::new(E1, D1, Move::QUIET);
+        let counter = Move::new(E1, G1, Move::KING_CASTLE);
+        let favourite = Move::new(H1, H5, Move::QUIET);
+        heuristics.add_history(Color::WHITE, favourite, 500);
+
+        let moves = picked(
+            &position,
+            MovePicker::new(hash_move, [killer, Move::NULL], counter),
+            &heuristics,
+        );
+
+        assert_eq!(moves[0], hash_move);
+        assert_eq!(moves[1], Move::new(E4, D5, Move::CAPTURE));
+        assert_eq!(moves[2], killer);
+        assert_eq!(moves[3], counter);
+        assert_eq!(moves[4], favourite);
+        assert_eq!(*moves.last().unwrap(), Move::new(B1, B5, Move::CAPTURE));
+    }
+
+    #[test]
+    fn test_captures_only() {
+        let position = Position::from_fen("4k3/8/2p5/1p1q4/4P3/8/8/1R2K2R w K - 0 1").unwrap();
+        let moves = picked(&position, MovePicker::captures(), &Heuristics::new());
+
+        assert_eq!(
+            moves,
+            vec![
+                Move::new(E4, D5, Move::CAPTURE),
+                Move::new(B1, B5, Move::CAPTURE)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_heuristics_update() {
+        let mut position = Position::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let mut heuristics = Heuristics::new();
+        position.make_move(Move::new(A1, A2, Move::QUIET));
+
+        let best = Move::new(E8, D8, Move::QUIET);
+        let tried = [Move::new(E8, F8, Move::QUIET), best];
+        heuristics.update(&position, best, 4, 3, &tried);
+
+        assert_eq!(heuristics.killers(3), [best, Move::NULL]);
+        assert_eq!(heuristics.counter_move(&position), best);
+        assert!(heuristics.history(Color::BLACK, best) > 0);
+        assert!(heuristics.history(Color::BLACK, tried[0]) < 0);
+        assert_eq!(heuristics.history(Color::WHITE, best), 0);
+
+        let other = Move::new(E8, E7, Move::QUIET);
+        heuristics.update(&position, other, 4, 3, &[other]);
+        assert_eq!(heuristics.killers(3), [other, best]);
+
+        // History saturates instead of growing without bound.
+        for _ in 0..10_000 {
+            heuristics.update(&position, best, 40, 3, &[best]);
+        }
+        assert!(heuristics.history(Color::BLACK, best) <= MAX_HISTORY);
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+
+use crate::piece::{Color, Piece, Piece::*};
+use crate::types::square::Square;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Move(u16);
+
+impl Move {
+    // Bits 0-5 hold the origin square, bits 6-11 the target square and bits 12-15 the flags.
+    pub const QUIET: u16 = 0;
+    pub const DOUBLE_PAWN_PUSH: u16 = 1;
+    pub const KING_CASTLE: u16 = 2;
+    pub const QUEEN_CASTLE: u16 = 3;
+    pub const CAPTURE: u16 = 4;
+    pub const EN_PASSANT: u16 = 5;
+    pub const KNIGHT_PROMOTION: u16 = 8;
+    pub const BISHOP_PROMOTION: u16 = 9;
+    pub const ROOK_PROMOTION: u16 = 10;
+    pub const QUEEN_PROMOTION: u16 = 11;
+
+    const PROMOTION_FLAG: u16 = 8;
+
+    pub const NULL: Move = Move(0);
+
+    pub const fn new(from: Square, to: Square, flags: u16) -> Move {
+        Move((from as u16) | ((to as u16) << 6) | (flags << 12))
+    }
+
+    pub const fn from_bits(bits: u16) -> Move {
+        Move(bits)
+    }
+
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    pub fn from(self) -> Square {
+        Square::try_from((self.0 & 0x3F) as u64).unwrap()
+    }
+
+    pub fn to(self) -> Square {
+        Square::try_from(((self.0 >> 6) & 0x3F) as u64).unwrap()
+    }
+
+    pub const fn flags(self) -> u16 {
+        self.0 >> 12
+    }
+
+    pub const fn is_null(self) -> bool {
+        self.0 == Move::NULL.0
+    }
+
+    pub const fn is_capture(self) -> bool {
+        self.flags() & Move::CAPTURE != 0
+    }
+
+    pub const fn is_en_passant(self) -> bool {
+        self.flags() == Move::EN_PASSANT
+    }
+
+    pub const fn is_double_pawn_push(self) -> bool {
+        self.flags() == Move::DOUBLE_PAWN_PUSH
+    }
+
+    pub const fn is_castle(self) -> bool {
+        self.flags() == Move::KING_CASTLE || self.flags() == Move::QUEEN_CASTLE
+    }
+
+    pub const fn is_promotion(self) -> bool {
+        self.flags() & Move::PROMOTION_FLAG != 0
+    }
+
+    pub fn promotion(self, color: Color) -> Option<Piece> {
+        if !self.is_promotion() {
+            return None;
+        }
+
+        match self.flags() & 3 {
+            0 => Some(KNIGHT(color)),
+            1 => Some(BISHOP(color)),
+            2 => Some(ROOK(color)),
+            _ => Some(QUEEN(color)),
+        }
+    }
+
+    pub fn to_uci(self) -> String {
+        // Long algebraic notation as used by the UCI protocol, e.g. e7e8q.
+        if self.is_null() {
+            return "0000".to_string();
+        }
+
+        match self.promotion(Color::BLACK) {
+            Some(piece) => format!("{}{}{}", self.from(), self.to(), piece.to_char()),
+            None => format!("{}{}", self.from(), self.to()),
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_uci())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::square::Square::*;
+
+    #[test]
+    fn test_move_encoding() {
+        let mv = Move::new(E2, E4, Move::DOUBLE_PAWN_PUSH);
+
+        assert_eq!(mv.from(), E2);
+        assert_eq!(mv.to(), E4);
+        assert!(mv.is_double_pawn_push());
+        assert!(!mv.is_capture());
+        assert!(!mv.is_promotion());
+
+        let mv = Move::new(H8, A1, Move::CAPTURE);
+        assert_eq!(mv.from(), H8);
+        assert_eq!(mv.to(), A1);
+        assert!(mv.is_capture());
+    }
+
+    #[test]
+    fn test_move_flags() {
+        assert!(Move::new(D5, E6, Move::EN_PASSANT).is_en_passant());
+        assert!(Move::new(D5, E6, Move::EN_PASSANT).is_capture());
+        assert!(Move::new(E1, G1, Move::KING_CASTLE).is_castle());
+        assert!(Move::new(E1, C1, Move::QUEEN_CASTLE).is_castle());
+        assert!(!Move::new(E1, F1, Move::QUIET).is_castle());
+
+        let mv = Move::new(B7, A8, Move::KNIGHT_PROMOTION | Move::CAPTURE);
+        assert!(mv.is_promotion());
+        assert!(mv.is_capture());
+        assert_eq!(mv.promotion(Color::WHITE), Some(KNIGHT(Color::WHITE)));
+
+        let mv = Move::new(B2, B1, Move::QUEEN_PROMOTION);
+        assert!(!mv.is_capture());
+        assert_eq!(mv.promotion(Color::BLACK), Some(QUEEN(Color::BLACK)));
+    }
+
+    #[test]
+    fn test_move_to_uci() {
+        assert_eq!(Move::new(E2, E4, Move::DOUBLE_PAWN_PUSH).to_uci(), "e2e4");
+        assert_eq!(Move::new(E7, E8, Move::ROOK_PROMOTION).to_uci(), "e7e8r");
+        assert_eq!(Move::NULL.to_string(), "0000");
+    }
+}
@@ -0,0 +1,234 @@
+use crate::attack_maps::pawn_attack::pawn_any_attacks;
+use crate::board::Board;
+use crate::constants::ZERO;
+use crate::eval::Tapered;
+use crate::piece::{Color, Piece::PAWN};
+use crate::types::bitboard::{
+    bitboard_squares, east_one, north_one, south_one, west_one, BitBoard,
+};
+use crate::types::square::Square;
+
+// Indexed by the rank relative to the pawn's own side, so the rank it promotes from is 6.
+const PASSED: [Tapered; 8] = [
+    Tapered::new(0, 0),
+    Tapered::new(2, 8),
+    Tapered::new(5, 14),
+    Tapered::new(12, 28),
+    Tapered::new(28, 52),
+    Tapered::new(50, 95),
+    Tapered::new(85, 150),
+    Tapered::new(0, 0),
+];
+const CONNECTED: [Tapered; 8] = [
+    Tapered::new(0, 0),
+    Tapered::new(4, 2),
+    Tapered::new(6, 4),
+    Tapered::new(9, 6),
+    Tapered::new(15, 12),
+    Tapered::new(28, 22),
+    Tapered::new(45, 38),
+    Tapered::new(0, 0),
+];
+const ISOLATED: Tapered = Tapered::new(-12, -16);
+const DOUBLED: Tapered = Tapered::new(-10, -24);
+const BACKWARD: Tapered = Tapered::new(-8, -12);
+
+// 2^14 entries of 24 bytes, a bit under 400 KiB.
+const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+fn relative_rank(color: Color, square: Square) -> usize {
+    match color {
+        Color::WHITE => square as usize / 8,
+        Color::BLACK => 7 - square as usize / 8,
+    }
+}
+
+// Ranks strictly in front of the square, seen from color.
+fn forward_ranks(color: Color, square: Square) -> BitBoard {
+    let rank = square.rank().unwrap();
+
+    match color {
+        Color::WHITE => !rank.mask_till(),
+        Color::BLACK => !rank.mask_from(),
+    }
+}
+
+fn adjacent_files(square: Square) -> BitBoard {
+    let file = square.file().unwrap().mask();
+
+    east_one(file) | west_one(file)
+}
+
+fn stop_square(color: Color, square: Square) -> BitBoard {
+    match color {
+        Color::WHITE => north_one(square.as_bitboard()),
+        Color::BLACK => south_one(square.as_bitboard()),
+    }
+}
+
+fn evaluate_side(board: &Board, color: Color) -> Tapered {
+    let own = board.piece_bit_board(PAWN(color));
+    let enemy = board.piece_bit_board(PAWN(color.oposite()));
+    let enemy_attacks = pawn_any_attacks(color.oposite(), enemy);
+    let mut score = Tapered::ZERO;
+
+    for square in bitboard_squares(own) {
+        let rank = relative_rank(color, square);
+        let forward = forward_ranks(color, square);
+        let neighbours = own & adjacent_files(square);
+        let front_span = forward & square.file().unwrap().mask();
+        let passed_span = front_span | forward & adjacent_files(square);
+
+        let doubled = own & front_span != ZERO;
+        let passed = !doubled && enemy & passed_span == ZERO;
+        let supported = pawn_any_attacks(color.oposite(), square.as_bitboard()) & own != ZERO;
+        let phalanx = neighbours & square.rank().unwrap().mask() != ZERO;
+        // No neighbour can come up to defend it, and advancing walks into a pawn attack.
+        let backward = neighbours != ZERO
+            && neighbours & !forward == ZERO
+            && stop_square(color, square) & enemy_attacks != ZERO;
+
+        if passed {
+            score += PASSED[rank];
+        }
+        if doubled {
+            score += DOUBLED;
+        }
+        if neighbours == ZERO {
+            score += ISOLATED;
+        } else if backward {
+            score += BACKWARD;
+        }
+        if supported || phalanx {
+            score += CONNECTED[rank];
+        }
+    }
+
+    score
+}
+
+// Pawn structure terms, signed so white is positive.
+pub fn evaluate_pawns(board: &Board) -> Tapered {
+    evaluate_side(board, Color::WHITE) - evaluate_side(board, Color::BLACK)
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct PawnEntry {
+    key: u64,
+    score: Tapered,
+}
+
+// Caches evaluate_pawns by the pawn-only Zobrist key, the pawns rarely change between nodes.
+// Each search has its own, so no synchronisation is needed.
+pub struct PawnTable {
+    entries: Box<[PawnEntry]>,
+}
+
+impl PawnTable {
+    pub fn new() -> PawnTable {
+        PawnTable {
+            entries: vec![PawnEntry::default(); PAWN_TABLE_SIZE].into_boxed_slice(),
+        }
+    }
+
+    pub fn probe(&mut self, key: u64, board: &Board) -> Tapered {
+        let entry = &mut self.entries[key as usize & (PAWN_TABLE_SIZE - 1)];
+
+        // Key zero is a position without pawns, which the empty entries score correctly too.
+        if entry.key != key {
+            *entry = PawnEntry {
+                key,
+                score: evaluate_pawns(board),
+            };
+        }
+
+        entry.score
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        PawnTable::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::DEFAULT_FEN;
+    use crate::position::Position;
+
+    fn pawns(fen: &str, color: Color) -> Tapered {
+        evaluate_side(Position::from_fen(fen).unwrap().board(), color)
+    }
+
+    #[test]
+    fn test_start_position() {
+        let position = Position::from_fen(DEFAULT_FEN).unwrap();
+
+        assert_eq!(evaluate_pawns(position.board()), Tapered::ZERO);
+        assert_eq!(
+            evaluate_side(position.board(), Color::WHITE),
+            CONNECTED[1] * 8
+        );
+    }
+
+    #[test]
+    fn test_passed() {
+        // No pawn can stop the e-pawn, until one stands in front of it on a neighbouring file.
+        assert_eq!(
+            pawns("4k3/8/8/4P3/8/8/8/4K3 w - - 0 1", Color::WHITE),
+            PASSED[4] + ISOLATED
+        );
+        assert_eq!(
+            pawns("4k3/3p4/8/4P3/8/8/8/4K3 w - - 0 1", Color::WHITE),
+            ISOLATED
+        );
+        assert_eq!(
+            pawns("4k3/8/8/8/8/4p3/8/4K3 w - - 0 1", Color::BLACK),
+            PASSED[5] + ISOLATED
+        );
+    }
+
+    #[test]
+    fn test_doubled_and_isolated() {
+        // Only the rear pawn counts as doubled, and the front one is passed.
+        assert_eq!(
+            pawns("4k3/8/8/8/4P3/4P3/8/4K3 w - - 0 1", Color::WHITE),
+            DOUBLED + ISOLATED * 2 + PASSED[3]
+        );
+    }
+
+    #[test]
+    fn test_backward() {
+        // d3 is behind its neighbour on e4 and its stop square d4 is hit by the c5 pawn.
+        assert_eq!(
+            pawns("4k3/8/8/2p5/4P3/3P4/8/4K3 w - - 0 1", Color::WHITE),
+            BACKWARD + PASSED[3] + CONNECTED[3]
+        );
+        // Without the c5 pawn d3 can just advance.
+        assert_eq!(
+            pawns("4k3/8/8/8/4P3/3P4/8/4K3 w - - 0 1", Color::WHITE),
+            PASSED[2] + PASSED[3] + CONNECTED[3]
+        );
+    }
+
+    #[test]
+    fn test_connected() {
+        // A phalanx: both pawns count.
+        assert_eq!(
+            pawns("4k3/8/8/8/3PP3/8/8/4K3 w - - 0 1", Color::WHITE),
+            (PASSED[3] + CONNECTED[3]) * 2
+        );
+    }
+
+    #[test]
+    fn test_pawn_table() {
+        let position = Position::from_fen("4k3/8/8/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+        let mut table = PawnTable::new();
+        let score = evaluate_pawns(position.board());
+
+        assert_eq!(table.probe(position.pawn_key(), position.board()), score);
+        assert_eq!(table.probe(position.pawn_key(), position.board()), score);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::moves::Move;
+use crate::position::Position;
+
+impl Position {
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        // Counts the leaf nodes of the legal move tree, the last ply is counted without being played.
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_moves();
+
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+
+        for mv in moves.iter() {
+            self.make_move(*mv);
+            nodes += self.perft(depth - 1);
+            self.unmake_move();
+        }
+
+        nodes
+    }
+
+    pub fn divide(&mut self, depth: u32) -> Vec<(Move, u64)> {
+        // Perft split by root move, handy for bisecting a wrong node count against another engine.
+        let moves = self.legal_moves();
+        let mut result = Vec::with_capacity(moves.len());
+
+        for mv in moves.iter() {
+            self.make_move(*mv);
+            result.push((*mv, self.perft(depth.saturating_sub(1))));
+            self.unmake_move();
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::constants::DEFAULT_FEN;
+    use crate::position::Position;
+
+    // Reference positions from the chessprogramming wiki perft results page.
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    const POSITION_4_MIRRORED: &str =
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+    const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+    const POSITION_6: &str =
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+    fn assert_perft(fen: &str, expected: &[u64]) {
+        let mut position = Position::from_fen(fen).unwrap();
+        let original = position.clone();
+
+        for (depth, nodes) in expected.iter().enumerate() {
+            assert_eq!(
+                position.perft(depth as u32 + 1),
+                *nodes,
+                "{} at depth {}",
+                fen,
+                depth + 1
+            );
+        }
+
+        assert_eq!(position, original);
+    }
+
+    #[test]
+    fn test_perft_start_position() {
+        assert_perft(DEFAULT_FEN, &[20, 400, 8902, 197281]);
+    }
+
+    #[test]
+    fn test_perft_kiwipete() {
+        assert_perft(KIWIPETE, &[48, 2039, 97862]);
+    }
+
+    #[test]
+    fn test_perft_position_3() {
+        assert_perft(POSITION_3, &[14, 191, 2812, 43238, 674624]);
+    }
+
+    #[test]
+    fn test_perft_position_4() {
+        assert_perft(POSITION_4, &[6, 264, 9467, 422333]);
+        assert_perft(POSITION_4_MIRRORED, &[6, 264, 9467, 422333]);
+    }
+
+    #[test]
+    fn test_perft_position_5() {
+        assert_perft(POSITION_5, &[44, 1486, 62379]);
+    }
+
+    #[test]
+    fn test_perft_position_6() {
+        assert_perft(POSITION_6, &[46, 2079, 89890]);
+    }
+
+    #[test]
+    fn test_perft_special_cases() {
+        // Short positions targeting en passant, castling and promotion corner cases.
+        for (fen, depth, nodes) in [
+            ("3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1", 6, 1134888),
+            ("8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1", 6, 1015133),
+            ("8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1", 6, 1440467),
+            ("5k2/8/8/8/8/8/8/4K2R w K - 0 1", 6, 661072),
+            ("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1", 6, 803711),
+            ("r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1", 4, 1274206),
+            ("r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1", 4, 1720476),
+            ("2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1", 6, 3821001),
+            ("8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1", 5, 1004658),
+            ("4k3/1P6/8/8/8/8/K7/8 w - - 0 1", 6, 217342),
+            ("8/P1k5/K7/8/8/8/8/8 w - - 0 1", 6, 92683),
+            ("K1k5/8/P7/8/8/8/8/8 w - - 0 1", 6, 2217),
+            ("8/k1P5/8/1K6/8/8/8/8 w - - 0 1", 7, 567584),
+            ("8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1", 4, 23527),
+        ] {
+            assert_eq!(
+                Position::from_fen(fen).unwrap().perft(depth),
+                nodes,
+                "{} at depth {}",
+                fen,
+                depth
+            );
+        }
+    }
+
+    #[test]
+    fn test_divide() {
+        let mut position = Position::from_fen(KIWIPETE).unwrap();
+        let divide = position.divide(2);
+
+        assert_eq!(divide.len(), 48);
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2039);
+        assert!(divide
+            .iter()
+            .any(|(mv, nodes)| mv.to_uci() == "e1g1" && *nodes == 43));
+    }
+}
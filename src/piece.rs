@@ -1,12 +1,26 @@
+use crate::attack_maps::king_attacks::KING_ATTACKS;
+use crate::attack_maps::knight_attacks::KNIGHT_ATTACKS;
+use crate::attack_maps::pawn_attack::{PAWN_ATTACKS, PAWN_PUSHES};
+use crate::attack_maps::sliders::{bishop_attacks, queen_attacks, rook_attacks};
+use crate::constants::{SEVEN_RANK, TWO_RANK, ZERO};
+use crate::types::bitboard::BitBoard;
+use crate::types::square::Square;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Color {
     BLACK,
     WHITE,
 }
 
+pub const COLOR_COUNT: usize = 2;
+
 use Color::*;
 
 impl Color {
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
     pub fn from_char(c: char) -> Self {
         if c.is_uppercase() {
             Color::WHITE
@@ -37,14 +51,56 @@ pub enum Piece {
 use Piece::*;
 
 impl Piece {
-    pub fn attacks() -> u64 {
-        0 as u64
-    }
     //Outputs pseudo legal attacks
+    pub fn attacks(self, square: Square, occupancy: BitBoard) -> BitBoard {
+        match self {
+            PAWN(color) => PAWN_ATTACKS[color.index()][square as usize],
+            KNIGHT(_) => KNIGHT_ATTACKS[square as usize],
+            BISHOP(_) => bishop_attacks(square, occupancy),
+            ROOK(_) => rook_attacks(square, occupancy),
+            QUEEN(_) => queen_attacks(square, occupancy),
+            KING(_) => KING_ATTACKS[square as usize],
+        }
+    }
 
-    pub fn push() -> u64 {
+    pub fn push(self, square: Square, occupancy: BitBoard) -> BitBoard {
         // only for Pawn all other Pieces have all moves same as attacks
-        0 as u64
+        let color = match self {
+            PAWN(color) => color,
+            _ => return self.attacks(square, occupancy) & !occupancy,
+        };
+
+        let single_push = PAWN_PUSHES[color.index()][square as usize] & !occupancy;
+        let start_rank = match color {
+            WHITE => TWO_RANK,
+            BLACK => SEVEN_RANK,
+        };
+
+        if single_push == ZERO || square.as_bitboard() & start_rank == ZERO {
+            return single_push;
+        }
+
+        let double_push = PAWN_PUSHES[color.index()][single_push.trailing_zeros() as usize];
+
+        single_push | (double_push & !occupancy)
+    }
+
+    pub const fn index(&self) -> usize {
+        // Matches the order of PIECE_SET.
+        match self {
+            PAWN(WHITE) => 0,
+            KNIGHT(WHITE) => 1,
+            BISHOP(WHITE) => 2,
+            ROOK(WHITE) => 3,
+            QUEEN(WHITE) => 4,
+            KING(WHITE) => 5,
+            PAWN(BLACK) => 6,
+            KNIGHT(BLACK) => 7,
+            BISHOP(BLACK) => 8,
+            ROOK(BLACK) => 9,
+            QUEEN(BLACK) => 10,
+            KING(BLACK) => 11,
+        }
     }
 
     pub fn color(&self) -> Color {
@@ -99,7 +155,9 @@ impl Piece {
     }
 }
 
-pub static PIECE_SET: [Piece; 12] = [
+pub const PIECE_COUNT: usize = 12;
+
+pub static PIECE_SET: [Piece; PIECE_COUNT] = [
     PAWN(WHITE),
     KNIGHT(WHITE),
     BISHOP(WHITE),
@@ -113,3 +171,69 @@ pub static PIECE_SET: [Piece; 12] = [
     QUEEN(BLACK),
     KING(BLACK),
 ];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::bitboard::bitboard_from_str;
+    use crate::types::square::Square::*;
+
+    #[test]
+    fn test_piece_attacks() {
+        assert_eq!(
+            PAWN(WHITE).attacks(E4, 0),
+            D5.as_bitboard() | F5.as_bitboard()
+        );
+        assert_eq!(PAWN(BLACK).attacks(A5, 0), B4.as_bitboard());
+        assert_eq!(
+            KNIGHT(BLACK).attacks(A1, 0),
+            B3.as_bitboard() | C2.as_bitboard()
+        );
+        assert_eq!(
+            KING(WHITE).attacks(H8, !0),
+            G8.as_bitboard() | G7.as_bitboard() | H7.as_bitboard()
+        );
+
+        let occupancy = D6.as_bitboard() | F4.as_bitboard();
+        let rook = bitboard_from_str(
+            "
+            ........
+            ........
+            ...x....
+            ...x....
+            xxx.xx..
+            ...x....
+            ...x....
+            ...x....
+        ",
+        )
+        .unwrap();
+
+        assert_eq!(ROOK(WHITE).attacks(D4, occupancy), rook);
+        assert_eq!(
+            QUEEN(BLACK).attacks(D4, occupancy),
+            rook | BISHOP(BLACK).attacks(D4, occupancy)
+        );
+    }
+
+    #[test]
+    fn test_pawn_push() {
+        assert_eq!(PAWN(WHITE).push(E2, 0), E3.as_bitboard() | E4.as_bitboard());
+        assert_eq!(PAWN(BLACK).push(C7, 0), C6.as_bitboard() | C5.as_bitboard());
+        assert_eq!(PAWN(WHITE).push(E3, 0), E4.as_bitboard());
+        assert_eq!(PAWN(WHITE).push(E2, E4.as_bitboard()), E3.as_bitboard());
+        assert_eq!(PAWN(WHITE).push(E2, E3.as_bitboard()), 0);
+        assert_eq!(PAWN(BLACK).push(H1, 0), 0);
+    }
+
+    #[test]
+    fn test_piece_push_excludes_occupied_squares() {
+        let occupancy = B3.as_bitboard();
+
+        assert_eq!(KNIGHT(WHITE).push(A1, occupancy), C2.as_bitboard());
+        assert_eq!(
+            ROOK(BLACK).push(A1, occupancy | A2.as_bitboard()),
+            ROOK(BLACK).attacks(A1, A2.as_bitboard()) & !A2.as_bitboard()
+        );
+    }
+}
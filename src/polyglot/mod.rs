@@ -0,0 +1,430 @@
+mod random;
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::attack_maps::pawn_attack::PAWN_ATTACKS;
+use crate::moves::Move;
+use crate::piece::{Color, Piece, Piece::*};
+use crate::position::{CastlingRights, Position};
+use crate::types::bitboard::bitboard_squares;
+
+pub use random::POLYGLOT_RANDOM;
+
+const CASTLING_OFFSET: usize = 768;
+const EN_PASSANT_OFFSET: usize = 772;
+const TURN_OFFSET: usize = 780;
+
+pub const ENTRY_SIZE: usize = 16;
+
+#[derive(Debug)]
+pub enum BookError {
+    Io(io::Error),
+    InvalidLength(usize),
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::Io(error) => write!(f, "{}", error),
+            BookError::InvalidLength(length) => write!(
+                f,
+                "book size {} is not a multiple of {} bytes",
+                length, ENTRY_SIZE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BookError {}
+
+impl From<io::Error> for BookError {
+    fn from(error: io::Error) -> Self {
+        BookError::Io(error)
+    }
+}
+
+fn piece_kind(piece: Piece) -> usize {
+    // Polyglot orders pieces as black pawn, white pawn, black knight, ... white king.
+    let (kind, color) = match piece {
+        PAWN(color) => (0, color),
+        KNIGHT(color) => (1, color),
+        BISHOP(color) => (2, color),
+        ROOK(color) => (3, color),
+        QUEEN(color) => (4, color),
+        KING(color) => (5, color),
+    };
+
+    2 * kind + (color == Color::WHITE) as usize
+}
+
+pub fn polyglot_key(position: &Position) -> u64 {
+    let board = position.board();
+    let color = position.side_to_move();
+    let mut key = 0;
+
+    for square in bitboard_squares(board.all_bit_boards()) {
+        let piece = board.piece_on_square(square).unwrap();
+        key ^= POLYGLOT_RANDOM[64 * piece_kind(piece) + square as usize];
+    }
+
+    for (i, rights) in [
+        CastlingRights::WHITE_KING_SIDE,
+        CastlingRights::WHITE_QUEEN_SIDE,
+        CastlingRights::BLACK_KING_SIDE,
+        CastlingRights::BLACK_QUEEN_SIDE,
+    ]
+    .iter()
+    .enumerate()
+    {
+        if position.castling_rights().contains(*rights) {
+            key ^= POLYGLOT_RANDOM[CASTLING_OFFSET + i];
+        }
+    }
+
+    // The en passant file only counts when a pawn of the side to move could capture there.
+    if let Some(en_passant) = position.en_passant() {
+        let attackers = PAWN_ATTACKS[color.oposite().index()][en_passant as usize]
+            & board.piece_bit_board(PAWN(color));
+
+        if attackers != 0 {
+            key ^= POLYGLOT_RANDOM[EN_PASSANT_OFFSET + (en_passant as usize & 7)];
+        }
+    }
+
+    if color == Color::WHITE {
+        key ^= POLYGLOT_RANDOM[TURN_OFFSET];
+    }
+
+    key
+}
+
+pub fn encode_move(mv: Move) -> u16 {
+    // Polyglot stores castling as the king capturing its own rook, e.g. e1h1 for e1g1.
+    let to = if mv.is_castle() {
+        let rook_file = if mv.flags() == Move::KING_CASTLE {
+            7
+        } else {
+            0
+        };
+        (mv.to() as u16 & !7) | rook_file
+    } else {
+        mv.to() as u16
+    };
+
+    let promotion = match mv.promotion(Color::WHITE) {
+        Some(KNIGHT(_)) => 1,
+        Some(BISHOP(_)) => 2,
+        Some(ROOK(_)) => 3,
+        Some(QUEEN(_)) => 4,
+        _ => 0,
+    };
+
+    to | ((mv.from() as u16) << 6) | (promotion << 12)
+}
+
+pub fn decode_move(position: &Position, raw: u16) -> Option<Move> {
+    // Book moves carry no flags, so they are matched against the legal moves of the position.
+    position
+        .legal_moves()
+        .iter()
+        .copied()
+        .find(|mv| encode_move(*mv) == raw)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    pub raw_move: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl BookEntry {
+    pub fn from_bytes(bytes: &[u8; ENTRY_SIZE]) -> BookEntry {
+        // All fields are stored big endian.
+        BookEntry {
+            key: u64::from_be_bytes(bytes[0..8].try_into().unwrap()),
+            raw_move: u16::from_be_bytes(bytes[8..10].try_into().unwrap()),
+            weight: u16::from_be_bytes(bytes[10..12].try_into().unwrap()),
+            learn: u32::from_be_bytes(bytes[12..16].try_into().unwrap()),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; ENTRY_SIZE] {
+        let mut bytes = [0; ENTRY_SIZE];
+
+        bytes[0..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.raw_move.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.learn.to_be_bytes());
+
+        bytes
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Book {
+    entries: Vec<BookEntry>,
+}
+
+impl Book {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Book, BookError> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(BookError::InvalidLength(bytes.len()));
+        }
+
+        let mut entries: Vec<BookEntry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|chunk| BookEntry::from_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        // Books are sorted by key already, this only guards against hand made files.
+        entries.sort_by_key(|entry| entry.key);
+
+        Ok(Book { entries })
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Book, BookError> {
+        Book::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_moves(moves: &[(Position, Move, u16)]) -> Book {
+        // Repeated position and move pairs are merged by adding up their weights.
+        let mut entries: Vec<BookEntry> = moves
+            .iter()
+            .map(|(position, mv, weight)| BookEntry {
+                key: polyglot_key(position),
+                raw_move: encode_move(*mv),
+                weight: *weight,
+                learn: 0,
+            })
+            .collect();
+
+        entries.sort_by_key(|entry| (entry.key, entry.raw_move));
+        entries.dedup_by(|entry, kept| {
+            let duplicate = entry.key == kept.key && entry.raw_move == kept.raw_move;
+            if duplicate {
+                kept.weight = kept.weight.saturating_add(entry.weight);
+            }
+            duplicate
+        });
+
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)));
+
+        Book { entries }
+    }
+
+    pub fn entries(&self) -> &[BookEntry] {
+        &self.entries
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for entry in &self.entries {
+            writer.write_all(&entry.to_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(self.entries.len() * ENTRY_SIZE);
+        self.write(&mut bytes)?;
+
+        fs::write(path, bytes)
+    }
+
+    fn entries_for(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+
+        &self.entries[start..end]
+    }
+
+    pub fn probe(&self, position: &Position) -> Vec<(Move, u16)> {
+        // Entries whose move is not legal in the position, e.g. after a key collision, are skipped.
+        self.entries_for(polyglot_key(position))
+            .iter()
+            .filter_map(|entry| Some((decode_move(position, entry.raw_move)?, entry.weight)))
+            .collect()
+    }
+
+    pub fn weighted_move(&self, position: &Position, random: u64) -> Option<Move> {
+        // Picks a move with probability proportional to its weight, `random` selects which one.
+        let moves = self.probe(position);
+        let total: u64 = moves.iter().map(|(_, weight)| *weight as u64).sum();
+
+        if total == 0 {
+            return moves.first().map(|(mv, _)| *mv);
+        }
+
+        let mut pick = random % total;
+
+        for (mv, weight) in moves {
+            if pick < weight as u64 {
+                return Some(mv);
+            }
+            pick -= weight as u64;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::DEFAULT_FEN;
+    use crate::types::square::Square::*;
+
+    fn play(moves: &[&str]) -> Position {
+        let mut position = Position::from_fen(DEFAULT_FEN).unwrap();
+
+        for uci in moves {
+            let mv = position
+                .legal_moves()
+                .iter()
+                .copied()
+                .find(|mv| mv.to_uci() == *uci)
+                .unwrap();
+            position.make_move(mv);
+        }
+
+        position
+    }
+
+    #[test]
+    fn test_reference_keys() {
+        // Keys from the Polyglot book format specification.
+        let cases: [(&[&str], u64); 9] = [
+            (&[], 0x463b96181691fc9c),
+            (&["e2e4"], 0x823c9b50fd114196),
+            (&["e2e4", "d7d5"], 0x0756b94461c50fb0),
+            (&["e2e4", "d7d5", "e4e5"], 0x662fafb965db29d4),
+            (&["e2e4", "d7d5", "e4e5", "f7f5"], 0x22a48b5a8e47ff78),
+            (
+                &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"],
+                0x652a607ca3f242c1,
+            ),
+            (
+                &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"],
+                0x00fdd303c946bdd9,
+            ),
+            (
+                &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"],
+                0x3c8123ea7b067637,
+            ),
+            (
+                &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"],
+                0x5c3f9b829b279560,
+            ),
+        ];
+
+        for (moves, key) in cases {
+            assert_eq!(polyglot_key(&play(moves)), key, "after {:?}", moves);
+        }
+    }
+
+    #[test]
+    fn test_encode_and_decode_moves() {
+        let position = Position::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        let castle = Move::new(E1, G1, Move::KING_CASTLE);
+        assert_eq!(encode_move(castle), (E1 as u16) << 6 | H1 as u16);
+        assert_eq!(decode_move(&position, encode_move(castle)), Some(castle));
+
+        let castle = Move::new(E1, C1, Move::QUEEN_CASTLE);
+        assert_eq!(encode_move(castle), (E1 as u16) << 6 | A1 as u16);
+        assert_eq!(decode_move(&position, encode_move(castle)), Some(castle));
+
+        let promotion = Move::new(B7, A8, Move::KNIGHT_PROMOTION | Move::CAPTURE);
+        assert_eq!(
+            encode_move(promotion),
+            1 << 12 | (B7 as u16) << 6 | A8 as u16
+        );
+        assert_eq!(
+            decode_move(&position, encode_move(promotion)),
+            Some(promotion)
+        );
+
+        assert_eq!(
+            decode_move(&position, encode_move(Move::new(E1, E3, 0))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_entry_bytes() {
+        let entry = BookEntry {
+            key: 0x463b96181691fc9c,
+            raw_move: 0x031c,
+            weight: 7,
+            learn: 0x01020304,
+        };
+        let bytes = entry.to_bytes();
+
+        assert_eq!(
+            &bytes[..8],
+            &[0x46, 0x3b, 0x96, 0x18, 0x16, 0x91, 0xfc, 0x9c]
+        );
+        assert_eq!(
+            &bytes[8..],
+            &[0x03, 0x1c, 0x00, 0x07, 0x01, 0x02, 0x03, 0x04]
+        );
+        assert_eq!(BookEntry::from_bytes(&bytes), entry);
+    }
+
+    #[test]
+    fn test_book_round_trip() {
+        let start = play(&[]);
+        let after_e4 = play(&["e2e4"]);
+        let e4 = Move::new(E2, E4, Move::DOUBLE_PAWN_PUSH);
+        let d4 = Move::new(D2, D4, Move::DOUBLE_PAWN_PUSH);
+        let c5 = Move::new(C7, C5, Move::DOUBLE_PAWN_PUSH);
+
+        let book = Book::from_moves(&[
+            (start.clone(), d4, 10),
+            (start.clone(), e4, 20),
+            (after_e4.clone(), c5, 5),
+            (start.clone(), d4, 15),
+        ]);
+
+        let mut bytes = Vec::new();
+        book.write(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 3 * ENTRY_SIZE);
+
+        let read = Book::from_bytes(&bytes).unwrap();
+        assert_eq!(read, book);
+        assert_eq!(read.probe(&start), vec![(d4, 25), (e4, 20)]);
+        assert_eq!(read.probe(&after_e4), vec![(c5, 5)]);
+        assert!(read.probe(&play(&["d2d4"])).is_empty());
+
+        assert_eq!(read.weighted_move(&start, 0), Some(d4));
+        assert_eq!(read.weighted_move(&start, 24), Some(d4));
+        assert_eq!(read.weighted_move(&start, 25), Some(e4));
+        assert_eq!(read.weighted_move(&start, 44), Some(e4));
+        assert_eq!(read.weighted_move(&start, 45), Some(d4));
+    }
+
+    #[test]
+    fn test_book_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("ruch-book-{}.bin", std::process::id()));
+        let book = Book::from_moves(&[(play(&[]), Move::new(G1, F3, Move::QUIET), 1)]);
+
+        book.save(&path).unwrap();
+        let read = Book::open(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.unwrap(), book);
+    }
+
+    #[test]
+    fn test_invalid_book_length() {
+        assert!(matches!(
+            Book::from_bytes(&[0; 17]),
+            Err(BookError::InvalidLength(17))
+        ));
+    }
+}
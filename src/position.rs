@@ -0,0 +1,641 @@
+use strum::IntoEnumIterator;
+
+use crate::board::Board;
+use crate::eval::{phase, piece_phase, piece_square, psqt, Tapered};
+use crate::moves::Move;
+use crate::piece::{Color, Piece, Piece::*};
+use crate::types::bitboard::bitboard_squares;
+use crate::types::square::{Square, Square::*};
+use crate::zobrist::ZOBRIST;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CastlingRights(u8);
+
+impl CastlingRights {
+    pub const NONE: CastlingRights = CastlingRights(0);
+    pub const WHITE_KING_SIDE: CastlingRights = CastlingRights(1);
+    pub const WHITE_QUEEN_SIDE: CastlingRights = CastlingRights(2);
+    pub const BLACK_KING_SIDE: CastlingRights = CastlingRights(4);
+    pub const BLACK_QUEEN_SIDE: CastlingRights = CastlingRights(8);
+    pub const WHITE_BOTH_SIDES: CastlingRights = CastlingRights(3);
+    pub const BLACK_BOTH_SIDES: CastlingRights = CastlingRights(12);
+    pub const ALL: CastlingRights = CastlingRights(15);
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub const fn contains(self, other: CastlingRights) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: CastlingRights) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: CastlingRights) {
+        self.0 &= !other.0;
+    }
+}
+
+fn castling_rights_lost(square: Square) -> CastlingRights {
+    // Moving from or capturing on one of these squares forfeits the matching rights.
+    match square {
+        E1 => CastlingRights::WHITE_BOTH_SIDES,
+        H1 => CastlingRights::WHITE_KING_SIDE,
+        A1 => CastlingRights::WHITE_QUEEN_SIDE,
+        E8 => CastlingRights::BLACK_BOTH_SIDES,
+        H8 => CastlingRights::BLACK_KING_SIDE,
+        A8 => CastlingRights::BLACK_QUEEN_SIDE,
+        _ => CastlingRights::NONE,
+    }
+}
+
+fn castling_rook_squares(mv: Move) -> (Square, Square) {
+    match (mv.to(), mv.flags()) {
+        (G1, Move::KING_CASTLE) => (H1, F1),
+        (C1, Move::QUEEN_CASTLE) => (A1, D1),
+        (G8, Move::KING_CASTLE) => (H8, F8),
+        (C8, Move::QUEEN_CASTLE) => (A8, D8),
+        _ => panic!("{} is not a castling move", mv),
+    }
+}
+
+pub fn en_passant_capture_square(mv: Move) -> Square {
+    // The captured pawn sits on the origin rank, in the file of the target square.
+    Square::try_from((mv.from() as u64 & !7) | (mv.to() as u64 & 7)).unwrap()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Undo {
+    mv: Move,
+    captured: Option<Piece>,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    key: u64,
+}
+
+impl Undo {
+    pub fn mv(&self) -> Move {
+        self.mv
+    }
+
+    pub fn captured(&self) -> Option<Piece> {
+        self.captured
+    }
+
+    // Zobrist key of the position before the move was made.
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    board: Board,
+    side_to_move: Color,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    key: u64,
+    // Zobrist key of the pawns alone, for the pawn hash table.
+    pawn_key: u64,
+    // Material and piece-square score and game phase, kept up to date like the key.
+    psqt: Tapered,
+    phase: i32,
+    history: Vec<Undo>,
+}
+
+impl Position {
+    pub fn new(
+        board: Board,
+        side_to_move: Color,
+        castling_rights: CastlingRights,
+        en_passant: Option<Square>,
+        halfmove_clock: u32,
+        fullmove_number: u32,
+    ) -> Position {
+        let mut position = Position {
+            board,
+            side_to_move,
+            castling_rights,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+            key: 0,
+            pawn_key: 0,
+            psqt: Tapered::ZERO,
+            phase: 0,
+            history: Vec::new(),
+        };
+
+        position.key = position.compute_key();
+        position.pawn_key = position.compute_pawn_key();
+        position.psqt = psqt(&position.board);
+        position.phase = phase(&position.board);
+        position
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn side_to_move(&self) -> Color {
+        self.side_to_move
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    pub fn history(&self) -> &[Undo] {
+        &self.history
+    }
+
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    pub fn pawn_key(&self) -> u64 {
+        self.pawn_key
+    }
+
+    pub fn psqt(&self) -> Tapered {
+        self.psqt
+    }
+
+    pub fn phase(&self) -> i32 {
+        self.phase
+    }
+
+    pub fn compute_key(&self) -> u64 {
+        // Zobrist key from scratch, make_move and unmake_move keep `key` equal to this.
+        let mut key =
+            ZOBRIST.castling_rights(self.castling_rights) ^ ZOBRIST.en_passant(self.en_passant);
+
+        if self.side_to_move == Color::BLACK {
+            key ^= ZOBRIST.black_to_move();
+        }
+
+        for square in Square::iter() {
+            if let Some(piece) = self.board.piece_on_square(square) {
+                key ^= ZOBRIST.piece(piece, square);
+            }
+        }
+
+        key
+    }
+
+    pub fn compute_pawn_key(&self) -> u64 {
+        [PAWN(Color::WHITE), PAWN(Color::BLACK)]
+            .iter()
+            .flat_map(|pawn| {
+                bitboard_squares(self.board.piece_bit_board(*pawn))
+                    .map(|square| ZOBRIST.piece(*pawn, square))
+            })
+            .fold(0, |key, piece_key| key ^ piece_key)
+    }
+
+    fn put_piece(&mut self, piece: Piece, square: Square) {
+        self.board
+            .put_piece_on_square(piece, square)
+            .unwrap_or_else(|_| panic!("Square {} is already occupied", square));
+        self.key ^= ZOBRIST.piece(piece, square);
+        if matches!(piece, PAWN(_)) {
+            self.pawn_key ^= ZOBRIST.piece(piece, square);
+        }
+        self.psqt += piece_square(piece, square);
+        self.phase += piece_phase(piece);
+    }
+
+    fn remove_piece(&mut self, square: Square) -> Option<Piece> {
+        let piece = self.board.remove_piece_from_square(square)?;
+        self.key ^= ZOBRIST.piece(piece, square);
+        if matches!(piece, PAWN(_)) {
+            self.pawn_key ^= ZOBRIST.piece(piece, square);
+        }
+        self.psqt -= piece_square(piece, square);
+        self.phase -= piece_phase(piece);
+
+        Some(piece)
+    }
+
+    fn set_castling_rights_and_en_passant(
+        &mut self,
+        castling_rights: CastlingRights,
+        en_passant: Option<Square>,
+    ) {
+        self.key ^= ZOBRIST.castling_rights(self.castling_rights)
+            ^ ZOBRIST.castling_rights(castling_rights)
+            ^ ZOBRIST.en_passant(self.en_passant)
+            ^ ZOBRIST.en_passant(en_passant);
+        self.castling_rights = castling_rights;
+        self.en_passant = en_passant;
+    }
+
+    pub fn make_move(&mut self, mv: Move) {
+        // Expects a pseudo-legal move for the side to move.
+        let color = self.side_to_move;
+        let key = self.key;
+        let piece = self
+            .remove_piece(mv.from())
+            .unwrap_or_else(|| panic!("No piece to move on {}", mv.from()));
+
+        let captured = if mv.is_en_passant() {
+            self.remove_piece(en_passant_capture_square(mv))
+        } else if mv.is_capture() {
+            self.remove_piece(mv.to())
+        } else {
+            None
+        };
+
+        self.history.push(Undo {
+            mv,
+            captured,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            key,
+        });
+
+        self.put_piece(mv.promotion(color).unwrap_or(piece), mv.to());
+
+        if mv.is_castle() {
+            let (rook_from, rook_to) = castling_rook_squares(mv);
+            let rook = self.remove_piece(rook_from).unwrap();
+            self.put_piece(rook, rook_to);
+        }
+
+        let mut castling_rights = self.castling_rights;
+        castling_rights.remove(castling_rights_lost(mv.from()));
+        castling_rights.remove(castling_rights_lost(mv.to()));
+
+        let en_passant = if mv.is_double_pawn_push() {
+            Square::try_from((mv.from() as u64 + mv.to() as u64) / 2).ok()
+        } else {
+            None
+        };
+
+        self.set_castling_rights_and_en_passant(castling_rights, en_passant);
+
+        if matches!(piece, PAWN(_)) || captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if color == Color::BLACK {
+            self.fullmove_number += 1;
+        }
+
+        self.side_to_move = color.oposite();
+        self.key ^= ZOBRIST.black_to_move();
+
+        debug_assert_eq!(
+            self.key,
+            self.compute_key(),
+            "Zobrist key out of sync after {}",
+            mv
+        );
+        debug_assert_eq!(self.pawn_key, self.compute_pawn_key());
+        debug_assert_eq!(self.psqt, psqt(&self.board));
+        debug_assert_eq!(self.phase, phase(&self.board));
+    }
+
+    pub fn make_null_move(&mut self) {
+        // Passes the turn, for the search to find out whether the opponent has a threat. The
+        // halfmove clock restarts so no repetition is detected across the null move.
+        self.history.push(Undo {
+            mv: Move::NULL,
+            captured: None,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            key: self.key,
+        });
+
+        self.set_castling_rights_and_en_passant(self.castling_rights, None);
+        self.halfmove_clock = 0;
+
+        if self.side_to_move == Color::BLACK {
+            self.fullmove_number += 1;
+        }
+
+        self.side_to_move = self.side_to_move.oposite();
+        self.key ^= ZOBRIST.black_to_move();
+    }
+
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let undo = self.history.pop()?;
+        let mv = undo.mv;
+        let color = self.side_to_move.oposite();
+
+        if mv.is_null() {
+            return self.unmake_null_move(undo);
+        }
+
+        let placed = self.remove_piece(mv.to()).unwrap();
+        let piece = if mv.is_promotion() {
+            PAWN(color)
+        } else {
+            placed
+        };
+        self.put_piece(piece, mv.from());
+
+        if let Some(captured) = undo.captured {
+            let square = if mv.is_en_passant() {
+                en_passant_capture_square(mv)
+            } else {
+                mv.to()
+            };
+            self.put_piece(captured, square);
+        }
+
+        if mv.is_castle() {
+            let (rook_from, rook_to) = castling_rook_squares(mv);
+            let rook = self.remove_piece(rook_to).unwrap();
+            self.put_piece(rook, rook_from);
+        }
+
+        if color == Color::BLACK {
+            self.fullmove_number -= 1;
+        }
+
+        self.side_to_move = color;
+        self.key ^= ZOBRIST.black_to_move();
+        self.set_castling_rights_and_en_passant(undo.castling_rights, undo.en_passant);
+        self.halfmove_clock = undo.halfmove_clock;
+
+        debug_assert_eq!(
+            self.key, undo.key,
+            "Zobrist key out of sync after undoing {}",
+            mv
+        );
+        debug_assert_eq!(self.key, self.compute_key());
+        debug_assert_eq!(self.psqt, psqt(&self.board));
+
+        Some(mv)
+    }
+
+    fn unmake_null_move(&mut self, undo: Undo) -> Option<Move> {
+        let color = self.side_to_move.oposite();
+
+        if color == Color::BLACK {
+            self.fullmove_number -= 1;
+        }
+
+        self.side_to_move = color;
+        self.key ^= ZOBRIST.black_to_move();
+        self.set_castling_rights_and_en_passant(undo.castling_rights, undo.en_passant);
+        self.halfmove_clock = undo.halfmove_clock;
+
+        debug_assert_eq!(self.key, undo.key);
+
+        Some(Move::NULL)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_make_unmake(fen: &str, mv: Move, expected_fen: &str) {
+        let mut position = Position::from_fen(fen).unwrap();
+        let original = position.clone();
+
+        position.make_move(mv);
+        assert_eq!(position.to_fen(), expected_fen);
+        assert_eq!(position.history().len(), 1);
+        assert_eq!(
+            position.key(),
+            Position::from_fen(expected_fen).unwrap().key()
+        );
+
+        assert_eq!(position.unmake_move(), Some(mv));
+        assert_eq!(position, original);
+    }
+
+    #[test]
+    fn test_quiet_move() {
+        assert_make_unmake(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            Move::new(G1, F3, Move::QUIET),
+            "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1",
+        );
+    }
+
+    #[test]
+    fn test_double_pawn_push_sets_en_passant() {
+        assert_make_unmake(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            Move::new(E2, E4, Move::DOUBLE_PAWN_PUSH),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        );
+        assert_make_unmake(
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            Move::new(C7, C5, Move::DOUBLE_PAWN_PUSH),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+        );
+    }
+
+    #[test]
+    fn test_capture() {
+        assert_make_unmake(
+            "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+            Move::new(E4, D5, Move::CAPTURE),
+            "rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+        );
+    }
+
+    #[test]
+    fn test_en_passant() {
+        assert_make_unmake(
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            Move::new(E5, F6, Move::EN_PASSANT),
+            "rnbqkbnr/ppp1p1pp/5P2/3p4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3",
+        );
+        assert_make_unmake(
+            "4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1",
+            Move::new(D4, E3, Move::EN_PASSANT),
+            "4k3/8/8/8/8/4p3/8/4K3 w - - 0 2",
+        );
+    }
+
+    #[test]
+    fn test_castling() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 10";
+
+        assert_make_unmake(
+            fen,
+            Move::new(E1, G1, Move::KING_CASTLE),
+            "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 4 10",
+        );
+        assert_make_unmake(
+            fen,
+            Move::new(E1, C1, Move::QUEEN_CASTLE),
+            "r3k2r/8/8/8/8/8/8/2KR3R b kq - 4 10",
+        );
+        assert_make_unmake(
+            "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 10",
+            Move::new(E8, G8, Move::KING_CASTLE),
+            "r4rk1/8/8/8/8/8/8/R3K2R w KQ - 4 11",
+        );
+        assert_make_unmake(
+            "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 10",
+            Move::new(E8, C8, Move::QUEEN_CASTLE),
+            "2kr3r/8/8/8/8/8/8/R3K2R w KQ - 4 11",
+        );
+    }
+
+    #[test]
+    fn test_rook_moves_and_captures_forfeit_castling_rights() {
+        assert_make_unmake(
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            Move::new(H1, H8, Move::CAPTURE),
+            "r3k2R/8/8/8/8/8/8/R3K3 b Qq - 0 1",
+        );
+        assert_make_unmake(
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            Move::new(A1, A2, Move::QUIET),
+            "r3k2r/8/8/8/8/8/R7/4K2R b Kkq - 1 1",
+        );
+    }
+
+    #[test]
+    fn test_promotion() {
+        assert_make_unmake(
+            "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+            Move::new(A7, A8, Move::QUEEN_PROMOTION),
+            "Qr2k3/8/8/8/8/8/8/4K3 b - - 0 1",
+        );
+        assert_make_unmake(
+            "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+            Move::new(A7, B8, Move::KNIGHT_PROMOTION | Move::CAPTURE),
+            "1N2k3/8/8/8/8/8/8/4K3 b - - 0 1",
+        );
+    }
+
+    #[test]
+    fn test_promotion_updates_evaluation() {
+        let mut position = Position::from_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let original = (position.psqt(), position.phase());
+
+        position.make_move(Move::new(A7, B8, Move::QUEEN_PROMOTION | Move::CAPTURE));
+
+        assert_eq!(position.phase(), 4);
+        assert_eq!(position.psqt(), psqt(position.board()));
+
+        position.unmake_move();
+
+        assert_eq!((position.psqt(), position.phase()), original);
+    }
+
+    #[test]
+    fn test_unmake_sequence_restores_start() {
+        let mut position =
+            Position::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let original = position.clone();
+
+        for mv in [
+            Move::new(E2, E4, Move::DOUBLE_PAWN_PUSH),
+            Move::new(D7, D5, Move::DOUBLE_PAWN_PUSH),
+            Move::new(E4, D5, Move::CAPTURE),
+            Move::new(D8, D5, Move::CAPTURE),
+            Move::new(B1, C3, Move::QUIET),
+        ] {
+            position.make_move(mv);
+        }
+
+        assert_eq!(
+            position.to_fen(),
+            "rnb1kbnr/ppp1pppp/8/3q4/8/2N5/PPPP1PPP/R1BQKBNR b KQkq - 1 3"
+        );
+
+        while position.unmake_move().is_some() {}
+
+        assert_eq!(position, original);
+    }
+
+    #[test]
+    fn test_null_move() {
+        let mut position =
+            Position::from_fen("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq e3 0 3")
+                .unwrap();
+        let original = position.clone();
+
+        position.make_null_move();
+        assert_eq!(
+            position.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR w KQkq - 0 4"
+        );
+        assert_eq!(position.key(), position.compute_key());
+
+        assert_eq!(position.unmake_move(), Some(Move::NULL));
+        assert_eq!(position, original);
+    }
+
+    #[test]
+    fn test_pawn_key_follows_pawns_only() {
+        let mut position =
+            Position::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let start = position.pawn_key();
+
+        position.make_move(Move::new(G1, F3, Move::QUIET));
+        assert_eq!(position.pawn_key(), start);
+
+        position.make_move(Move::new(E7, E5, Move::DOUBLE_PAWN_PUSH));
+        assert_ne!(position.pawn_key(), start);
+        assert_eq!(position.pawn_key(), position.compute_pawn_key());
+    }
+
+    #[test]
+    fn test_key_after_transposition() {
+        let mut a =
+            Position::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let mut b = a.clone();
+
+        for mv in [
+            Move::new(G1, F3, Move::QUIET),
+            Move::new(G8, F6, Move::QUIET),
+            Move::new(B1, C3, Move::QUIET),
+        ] {
+            a.make_move(mv);
+        }
+
+        for mv in [
+            Move::new(B1, C3, Move::QUIET),
+            Move::new(G8, F6, Move::QUIET),
+            Move::new(G1, F3, Move::QUIET),
+        ] {
+            b.make_move(mv);
+        }
+
+        assert_eq!(a.key(), b.key());
+        assert_eq!(a.key(), a.compute_key());
+    }
+
+    #[test]
+    fn test_key_covers_side_castling_and_en_passant() {
+        let key = |fen: &str| Position::from_fen(fen).unwrap().key();
+        let base = key("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq - 0 1");
+
+        assert_ne!(base, key("r3k2r/8/8/3pP3/8/8/8/R3K2R b KQkq - 0 1"));
+        assert_ne!(base, key("r3k2r/8/8/3pP3/8/8/8/R3K2R w Kkq - 0 1"));
+        assert_ne!(base, key("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1"));
+        assert_eq!(base, key("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq - 12 40"));
+    }
+}
@@ -0,0 +1,1129 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::constants::ZERO;
+use crate::eval::{evaluate_with, piece_value, PAWN_VALUE};
+use crate::movegen::{is_legal, MoveList};
+use crate::movepick::{is_quiet, Heuristics, MovePicker};
+use crate::moves::Move;
+use crate::pawns::PawnTable;
+use crate::piece::Piece::*;
+use crate::position::Position;
+use crate::tt::{Bound, TranspositionTable, TtEntry};
+
+pub const MAX_PLY: usize = 128;
+pub const MATE: i32 = 32_000;
+pub const INFINITY: i32 = MATE + 1;
+
+// Scores beyond this bound encode a forced mate, the distance to it being MATE - |score| plies.
+pub(crate) const MATE_BOUND: i32 = MATE - MAX_PLY as i32;
+
+// A capture that cannot lift the score to alpha even with this much on top is not searched.
+const DELTA_MARGIN: i32 = 200;
+
+// Reverse futility pruning below this depth, when the static evaluation beats beta by the margin
+// per ply.
+const RFP_DEPTH: i32 = 6;
+const RFP_MARGIN: i32 = 80;
+
+// Null move pruning from this depth on, checked by a search without null moves from the second.
+const NMP_DEPTH: i32 = 3;
+const NMP_VERIFY_DEPTH: i32 = 10;
+
+// Futility pruning of quiet moves up to this depth, margin per ply.
+const FUTILITY_DEPTH: i32 = 3;
+const FUTILITY_MARGIN: i32 = 150;
+
+// Late move pruning up to this depth, after 3 + depth^2 quiet moves.
+const LMP_DEPTH: i32 = 4;
+
+// Late move reductions from this depth on, for the moves after the first few.
+const LMR_DEPTH: i32 = 3;
+const LMR_MOVES: usize = 3;
+
+// Singular extensions from this depth on. The other moves must fail low against the hash move's
+// score minus the margin per ply.
+const SINGULAR_DEPTH: i32 = 8;
+const SINGULAR_MARGIN: i32 = 2;
+
+// How many nodes may pass between two looks at the clock and the stop flag.
+const CHECK_INTERVAL: u64 = 2048;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    CP(i32),
+    // Moves, not plies, until mate. Negative when the side to move gets mated.
+    MATE(i32),
+}
+
+impl Score {
+    pub fn from_value(value: i32) -> Score {
+        if value >= MATE_BOUND {
+            Score::MATE((MATE - value + 1) / 2)
+        } else if value <= -MATE_BOUND {
+            Score::MATE(-(MATE + value) / 2)
+        } else {
+            Score::CP(value)
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::CP(cp) => write!(f, "cp {}", cp),
+            Score::MATE(moves) => write!(f, "mate {}", moves),
+        }
+    }
+}
+
+// Switches for the selective parts of the search, to measure what each one is worth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    pub null_move: bool,
+    pub late_move_reductions: bool,
+    pub reverse_futility: bool,
+    pub futility: bool,
+    pub late_move_pruning: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            null_move: true,
+            late_move_reductions: true,
+            reverse_futility: true,
+            futility: true,
+            late_move_pruning: true,
+        }
+    }
+}
+
+impl SearchConfig {
+    // Plain alpha-beta, every move searched to full depth.
+    pub const NONE: SearchConfig = SearchConfig {
+        null_move: false,
+        late_move_reductions: false,
+        reverse_futility: false,
+        futility: false,
+        late_move_pruning: false,
+    };
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+    // Only these moves are considered at the root, all of them when empty.
+    pub searchmoves: Vec<Move>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub best_move: Move,
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    // Permille of the transposition table in use, as UCI reports it.
+    pub hashfull: u32,
+    pub pv: Vec<Move>,
+}
+
+impl SearchResult {
+    pub fn score(&self) -> Score {
+        Score::from_value(self.score)
+    }
+}
+
+pub struct Search {
+    limits: SearchLimits,
+    config: SearchConfig,
+    stop: Arc<AtomicBool>,
+    tt: Arc<TranspositionTable>,
+    heuristics: Box<Heuristics>,
+    pawns: PawnTable,
+    start: Instant,
+    nodes: u64,
+    aborted: bool,
+    // Set during a null move verification search, which must not try null moves itself.
+    verifying: bool,
+    // Per ply, the move left out by a singular extension search.
+    excluded: [Move; MAX_PLY],
+    root_depth: u32,
+    // Triangular PV table: row ply holds the principal variation found from that ply on.
+    pv_table: Box<[[Move; MAX_PLY]; MAX_PLY]>,
+    pv_length: [usize; MAX_PLY],
+}
+
+impl Search {
+    pub fn new(limits: SearchLimits) -> Search {
+        Search::with_stop(limits, Arc::new(AtomicBool::new(false)))
+    }
+
+    // The search gives up as soon as it notices stop being set, e.g. from another thread.
+    pub fn with_stop(limits: SearchLimits, stop: Arc<AtomicBool>) -> Search {
+        Search::with_table(limits, stop, Arc::new(TranspositionTable::default()))
+    }
+
+    // Searches sharing a table, one after the other or side by side, profit from each other's work.
+    pub fn with_table(
+        limits: SearchLimits,
+        stop: Arc<AtomicBool>,
+        tt: Arc<TranspositionTable>,
+    ) -> Search {
+        Search {
+            limits,
+            config: SearchConfig::default(),
+            stop,
+            tt,
+            heuristics: Heuristics::new(),
+            pawns: PawnTable::new(),
+            start: Instant::now(),
+            nodes: 0,
+            aborted: false,
+            verifying: false,
+            excluded: [Move::NULL; MAX_PLY],
+            root_depth: 0,
+            pv_table: Box::new([[Move::NULL; MAX_PLY]; MAX_PLY]),
+            pv_length: [0; MAX_PLY],
+        }
+    }
+
+    pub fn set_config(&mut self, config: SearchConfig) {
+        self.config = config;
+    }
+
+    pub fn run(&mut self, position: &Position) -> SearchResult {
+        self.run_with(position, |_| {})
+    }
+
+    // Iterative deepening. on_iteration sees the result of every completed depth.
+    pub fn run_with<F>(&mut self, position: &Position, mut on_iteration: F) -> SearchResult
+    where
+        F: FnMut(&SearchResult),
+    {
+        let mut position = position.clone();
+        let max_depth = self
+            .limits
+            .depth
+            .unwrap_or(MAX_PLY as u32)
+            .clamp(1, MAX_PLY as u32 - 1);
+
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.aborted = false;
+        self.tt.new_search();
+
+        // Unknown or illegal searchmoves are dropped, if none are left every move is searched.
+        let legal_moves = position.legal_moves();
+        self.limits
+            .searchmoves
+            .retain(|mv| legal_moves.contains(mv));
+
+        let mut result = SearchResult {
+            best_move: legal_moves
+                .iter()
+                .copied()
+                .find(|mv| !self.is_excluded(0, *mv))
+                .unwrap_or(Move::NULL),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            elapsed: Duration::ZERO,
+            hashfull: 0,
+            pv: Vec::new(),
+        };
+
+        if result.best_move.is_null() {
+            result.score = if position.in_check() { -MATE } else { 0 };
+            return result;
+        }
+
+        for depth in 1..=max_depth {
+            self.root_depth = depth;
+            let score = self.negamax(&mut position, depth as i32, -INFINITY, INFINITY, 0);
+
+            if self.aborted {
+                break;
+            }
+
+            result = SearchResult {
+                best_move: self.pv_table[0][0],
+                score,
+                depth,
+                nodes: self.nodes,
+                elapsed: self.start.elapsed(),
+                hashfull: self.tt.hashfull(),
+                pv: self.pv_table[0][..self.pv_length[0]].to_vec(),
+            };
+            on_iteration(&result);
+
+            // Another iteration costs more than all previous ones, so it would not finish anyway.
+            if let Some(time) = self.limits.time {
+                if self.start.elapsed() >= time / 2 {
+                    break;
+                }
+            }
+        }
+
+        result.nodes = self.nodes;
+        result.elapsed = self.start.elapsed();
+        result
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.aborted {
+            return true;
+        }
+
+        if self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes) {
+            self.aborted = true;
+        } else if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || self
+                    .limits
+                    .time
+                    .is_some_and(|time| self.start.elapsed() >= time);
+        }
+
+        self.aborted
+    }
+
+    fn negamax(
+        &mut self,
+        position: &mut Position,
+        depth: i32,
+        mut alpha: i32,
+        mut beta: i32,
+        ply: usize,
+    ) -> i32 {
+        self.pv_length[ply] = ply;
+        let pv_node = beta - alpha > 1;
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        if depth <= 0 {
+            return self.quiescence(position, alpha, beta, ply);
+        }
+
+        self.nodes += 1;
+
+        if ply > 0 && is_draw(position) {
+            return 0;
+        }
+
+        if ply >= MAX_PLY - 1 {
+            return evaluate_with(position, &mut self.pawns);
+        }
+
+        // Mate distance pruning: no line from here can beat a mate already found closer to the root.
+        alpha = alpha.max(-MATE + ply as i32);
+        beta = beta.min(MATE - ply as i32 - 1);
+        if alpha >= beta {
+            return alpha;
+        }
+
+        let key = position.key();
+        let hit = self.tt.probe(key, ply);
+
+        // With moves excluded this is a different search of the same position, whose table
+        // entry does not apply and must not be overwritten.
+        let excluding = self.has_exclusions(ply);
+
+        // Outside the principal variation a stored bound settles the node. On it the search goes on
+        // so the PV stays complete.
+        if let Some(entry) =
+            hit.filter(|entry| !pv_node && !excluding && entry.depth as i32 >= depth)
+        {
+            let cutoff = match entry.bound {
+                Bound::EXACT => true,
+                Bound::LOWER => entry.score >= beta,
+                Bound::UPPER => entry.score <= alpha,
+            };
+
+            if cutoff {
+                return entry.score;
+            }
+        }
+
+        let in_check = position.in_check();
+        let static_eval = if in_check {
+            -INFINITY
+        } else {
+            evaluate_with(position, &mut self.pawns)
+        };
+
+        if !pv_node && !in_check && !excluding {
+            if let Some(score) = self.prune_node(position, depth, beta, ply, static_eval) {
+                return score;
+            }
+        }
+
+        // The best move of an earlier search of this position is the likeliest to cut off again.
+        let tt_move = hit.map_or(Move::NULL, |entry| entry.mv);
+        let mut picker = MovePicker::new(
+            tt_move,
+            self.heuristics.killers(ply),
+            self.heuristics.counter_move(position),
+        );
+
+        // Quiet moves that cannot lift a hopeless static evaluation up to alpha are skipped.
+        let futile = self.config.futility
+            && !pv_node
+            && !in_check
+            && depth <= FUTILITY_DEPTH
+            && alpha > -MATE_BOUND
+            && static_eval + FUTILITY_MARGIN * depth <= alpha;
+        let late_move_limit = if self.config.late_move_pruning
+            && !pv_node
+            && !in_check
+            && depth <= LMP_DEPTH
+            && alpha > -MATE_BOUND
+        {
+            (3 + depth * depth) as usize
+        } else {
+            usize::MAX
+        };
+
+        let original_alpha = alpha;
+        let mut best_move = Move::NULL;
+        let mut legal_moves = 0;
+        let mut moves_searched = 0;
+        let mut quiets_tried = MoveList::new();
+
+        while let Some(mv) = picker.next(position, &self.heuristics) {
+            if !is_legal(position, mv) {
+                continue;
+            }
+            legal_moves += 1;
+
+            if self.is_excluded(ply, mv) {
+                continue;
+            }
+
+            let quiet = is_quiet(mv);
+            if quiet && quiets_tried.len() >= late_move_limit {
+                continue;
+            }
+
+            // A move far better than all others is searched deeper, so is a recapture.
+            let singular =
+                mv == tt_move && !excluding && self.is_singular(position, depth, ply, hit);
+            let recapture = mv.is_capture()
+                && position
+                    .history()
+                    .last()
+                    .is_some_and(|undo| undo.captured().is_some() && undo.mv().to() == mv.to());
+
+            if self.aborted {
+                return 0;
+            }
+
+            position.make_move(mv);
+            let gives_check = position.in_check();
+
+            if futile && quiet && !gives_check && moves_searched > 0 {
+                position.unmake_move();
+                continue;
+            }
+
+            // Forcing moves get one ply more, as long as the line is not already twice as long
+            // as the iteration's depth.
+            let extension = ((singular || gives_check || recapture)
+                && ply < 2 * self.root_depth as usize) as i32;
+            let depth = depth + extension;
+
+            let score = if moves_searched == 0 {
+                -self.negamax(position, depth - 1, -beta, -alpha, ply + 1)
+            } else {
+                // Principal variation search: a null window proves the move no better than the
+                // first, late quiet moves at a reduced depth. Should that fail, search it properly.
+                let reduction = if self.config.late_move_reductions
+                    && depth >= LMR_DEPTH
+                    && moves_searched >= LMR_MOVES
+                    && quiet
+                    && !in_check
+                    && !gives_check
+                {
+                    let mut reduction = late_move_reduction(depth, moves_searched);
+                    reduction -= pv_node as i32;
+                    reduction -= self.heuristics.killers(ply).contains(&mv) as i32;
+                    reduction.clamp(0, depth - 2)
+                } else {
+                    0
+                };
+
+                let mut score =
+                    -self.negamax(position, depth - 1 - reduction, -alpha - 1, -alpha, ply + 1);
+
+                if score > alpha && reduction > 0 {
+                    score = -self.negamax(position, depth - 1, -alpha - 1, -alpha, ply + 1);
+                }
+
+                if score > alpha && score < beta {
+                    score = -self.negamax(position, depth - 1, -beta, -alpha, ply + 1);
+                }
+
+                score
+            };
+
+            position.unmake_move();
+            moves_searched += 1;
+
+            if self.aborted {
+                return 0;
+            }
+
+            if score > alpha {
+                alpha = score;
+                best_move = mv;
+
+                self.update_pv(ply, mv);
+
+                if alpha >= beta {
+                    if quiet {
+                        self.heuristics
+                            .update(position, mv, depth, ply, &quiets_tried);
+                    }
+                    break;
+                }
+            }
+
+            if quiet {
+                quiets_tried.push(mv);
+            }
+        }
+
+        if legal_moves == 0 {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+
+        if excluding {
+            return alpha;
+        }
+
+        let bound = if alpha >= beta {
+            Bound::LOWER
+        } else if alpha > original_alpha {
+            Bound::EXACT
+        } else {
+            Bound::UPPER
+        };
+
+        self.tt.store(
+            key,
+            TtEntry {
+                mv: best_move,
+                score: alpha,
+                depth: depth.min(u8::MAX as i32) as u8,
+                bound,
+            },
+            ply,
+        );
+
+        alpha
+    }
+
+    fn has_exclusions(&self, ply: usize) -> bool {
+        !self.excluded[ply].is_null() || (ply == 0 && !self.limits.searchmoves.is_empty())
+    }
+
+    // Moves left out of this node: the move tested for singularity, at the root also every move
+    // not among the searchmoves.
+    fn is_excluded(&self, ply: usize, mv: Move) -> bool {
+        mv == self.excluded[ply]
+            || (ply == 0
+                && !self.limits.searchmoves.is_empty()
+                && !self.limits.searchmoves.contains(&mv))
+    }
+
+    // The hash move is singular when every other move, searched at reduced depth, fails low
+    // against a bound somewhat below the hash move's score.
+    fn is_singular(
+        &mut self,
+        position: &mut Position,
+        depth: i32,
+        ply: usize,
+        hit: Option<TtEntry>,
+    ) -> bool {
+        let entry = match hit {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        if ply == 0
+            || depth < SINGULAR_DEPTH
+            || entry.bound == Bound::UPPER
+            || entry.depth as i32 + 3 < depth
+            || entry.score.abs() >= MATE_BOUND
+        {
+            return false;
+        }
+
+        let singular_beta = entry.score - SINGULAR_MARGIN * depth;
+
+        self.excluded[ply] = entry.mv;
+        let score = self.negamax(
+            position,
+            (depth - 1) / 2,
+            singular_beta - 1,
+            singular_beta,
+            ply,
+        );
+        self.excluded[ply] = Move::NULL;
+        self.pv_length[ply] = ply;
+
+        score < singular_beta
+    }
+
+    // Node level pruning, for nodes off the principal variation and not in check. Returns the
+    // score to fail high with.
+    fn prune_node(
+        &mut self,
+        position: &mut Position,
+        depth: i32,
+        beta: i32,
+        ply: usize,
+        static_eval: i32,
+    ) -> Option<i32> {
+        // Reverse futility: so far above beta that even a quiet move from the opponent won't
+        // bring it back.
+        if self.config.reverse_futility
+            && depth <= RFP_DEPTH
+            && beta.abs() < MATE_BOUND
+            && static_eval - RFP_MARGIN * depth >= beta
+        {
+            return Some(static_eval);
+        }
+
+        // Null move: if passing still fails high, a real move surely will. Never twice in a row,
+        // and never without pieces, where zugzwang makes passing the best move there is.
+        let last_move_null = position
+            .history()
+            .last()
+            .is_some_and(|undo| undo.mv().is_null());
+
+        if !self.config.null_move
+            || self.verifying
+            || depth < NMP_DEPTH
+            || static_eval < beta
+            || last_move_null
+            || !has_non_pawn_material(position)
+        {
+            return None;
+        }
+
+        let reduction = 3 + depth / 4 + ((static_eval - beta) / 200).min(3);
+
+        position.make_null_move();
+        let score = -self.negamax(position, depth - 1 - reduction, -beta, -beta + 1, ply + 1);
+        position.unmake_move();
+
+        if self.aborted || score < beta {
+            return None;
+        }
+
+        // A mate found after passing proves nothing.
+        let score = if score >= MATE_BOUND { beta } else { score };
+
+        if depth < NMP_VERIFY_DEPTH {
+            return Some(score);
+        }
+
+        // Deep down a wrong null move cutoff costs too much, a search without one confirms it.
+        self.verifying = true;
+        let verified = self.negamax(position, depth - 1 - reduction, beta - 1, beta, ply);
+        self.verifying = false;
+        self.pv_length[ply] = ply;
+
+        (!self.aborted && verified >= beta).then_some(score)
+    }
+
+    // Resolves captures until the position is quiet, so the horizon does not cut an exchange in half.
+    fn quiescence(
+        &mut self,
+        position: &mut Position,
+        mut alpha: i32,
+        beta: i32,
+        ply: usize,
+    ) -> i32 {
+        self.pv_length[ply] = ply;
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        self.nodes += 1;
+
+        if ply >= MAX_PLY - 1 {
+            return evaluate_with(position, &mut self.pawns);
+        }
+
+        // In check every evasion has to be tried, standing pat is no option.
+        let in_check = position.in_check();
+        let mut stand_pat = -INFINITY;
+
+        let mut picker = if in_check {
+            MovePicker::new(Move::NULL, [Move::NULL; 2], Move::NULL)
+        } else {
+            stand_pat = evaluate_with(position, &mut self.pawns);
+
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+
+            MovePicker::captures()
+        };
+
+        let mut legal_moves = 0;
+
+        while let Some(mv) = picker.next(position, &self.heuristics) {
+            if !is_legal(position, mv) {
+                continue;
+            }
+            legal_moves += 1;
+
+            if !in_check && stand_pat + gain(position, mv) + DELTA_MARGIN <= alpha {
+                continue;
+            }
+
+            // Captures that lose material in the exchange cannot raise alpha over stand pat.
+            if !in_check && !position.see_ge(mv, 0) {
+                continue;
+            }
+
+            position.make_move(mv);
+            let score = -self.quiescence(position, -beta, -alpha, ply + 1);
+            position.unmake_move();
+
+            if self.aborted {
+                return 0;
+            }
+
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply, mv);
+
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        if in_check && legal_moves == 0 {
+            return -MATE + ply as i32;
+        }
+
+        alpha
+    }
+
+    fn update_pv(&mut self, ply: usize, mv: Move) {
+        self.pv_table[ply][ply] = mv;
+        for next in ply + 1..self.pv_length[ply + 1] {
+            self.pv_table[ply][next] = self.pv_table[ply + 1][next];
+        }
+        self.pv_length[ply] = self.pv_length[ply + 1].max(ply + 1);
+    }
+}
+
+// Material a move wins at most: the captured piece plus what a pawn turns into.
+fn gain(position: &Position, mv: Move) -> i32 {
+    let color = position.side_to_move();
+    let captured = if mv.is_en_passant() {
+        Some(PAWN(color))
+    } else {
+        position.board().piece_on_square(mv.to())
+    };
+
+    let promotion = mv
+        .promotion(color)
+        .map_or(0, |piece| piece_value(piece) - PAWN_VALUE);
+
+    captured.map_or(0, piece_value) + promotion
+}
+
+fn has_non_pawn_material(position: &Position) -> bool {
+    let board = position.board();
+    let color = position.side_to_move();
+
+    [KNIGHT(color), BISHOP(color), ROOK(color), QUEEN(color)]
+        .iter()
+        .any(|piece| board.piece_bit_board(*piece) != ZERO)
+}
+
+// Reduction for the moves_searched'th move at depth, growing with the log of both.
+fn late_move_reduction(depth: i32, moves_searched: usize) -> i32 {
+    static TABLE: OnceLock<[[u8; 64]; 64]> = OnceLock::new();
+
+    let table = TABLE.get_or_init(|| {
+        let mut table = [[0; 64]; 64];
+
+        for (depth, row) in table.iter_mut().enumerate().skip(1) {
+            for (moves, reduction) in row.iter_mut().enumerate().skip(1) {
+                *reduction = (0.75 + (depth as f64).ln() * (moves as f64).ln() / 2.25) as u8;
+            }
+        }
+
+        table
+    });
+
+    table[(depth as usize).min(63)][moves_searched.min(63)] as i32
+}
+
+// Fifty move rule or a repetition of any position since the last irreversible move.
+fn is_draw(position: &Position) -> bool {
+    if position.halfmove_clock() >= 100 {
+        return true;
+    }
+
+    // Undo keys are taken before each move, so every second one has the same side to move.
+    position
+        .history()
+        .iter()
+        .rev()
+        .take(position.halfmove_clock() as usize)
+        .skip(1)
+        .step_by(2)
+        .any(|undo| undo.key() == position.key())
+}
+
+pub fn search(position: &Position, limits: SearchLimits) -> SearchResult {
+    Search::new(limits).run(position)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::DEFAULT_FEN;
+    use crate::eval::{evaluate, QUEEN_VALUE};
+    use crate::types::square::Square;
+
+    fn depth(depth: u32) -> SearchLimits {
+        SearchLimits {
+            depth: Some(depth),
+            ..SearchLimits::default()
+        }
+    }
+
+    fn line(result: &SearchResult) -> Vec<String> {
+        result.pv.iter().map(|mv| mv.to_uci()).collect()
+    }
+
+    #[test]
+    fn test_score_display() {
+        assert_eq!(Score::from_value(35).to_string(), "cp 35");
+        assert_eq!(Score::from_value(-120).to_string(), "cp -120");
+        assert_eq!(Score::from_value(MATE - 1).to_string(), "mate 1");
+        assert_eq!(Score::from_value(MATE - 3).to_string(), "mate 2");
+        assert_eq!(Score::from_value(-MATE + 2).to_string(), "mate -1");
+    }
+
+    #[test]
+    fn test_finds_mate_in_one() {
+        let position = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let result = search(&position, depth(3));
+
+        assert_eq!(result.best_move.to_uci(), "a1a8");
+        assert_eq!(result.score(), Score::MATE(1));
+        assert_eq!(line(&result), vec!["a1a8"]);
+    }
+
+    #[test]
+    fn test_finds_mate_in_two() {
+        let position = Position::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let result = search(&position, depth(4));
+
+        assert_eq!(result.score(), Score::MATE(2));
+        assert_eq!(result.pv.len(), 3);
+        assert_eq!(result.pv[0], result.best_move);
+    }
+
+    #[test]
+    fn test_sees_getting_mated() {
+        let position = Position::from_fen("6k1/8/8/8/8/8/rr6/7K w - - 0 1").unwrap();
+        let result = search(&position, depth(3));
+
+        assert_eq!(result.score(), Score::MATE(-1));
+    }
+
+    #[test]
+    fn test_wins_material() {
+        let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let result = search(&position, depth(3));
+
+        assert_eq!(result.best_move.to_uci(), "d2d5");
+        assert!(result.score > 0);
+    }
+
+    #[test]
+    fn test_quiescence_sees_recapture() {
+        // At depth one Qxd5 looks like it wins a pawn, the quiescence search sees exd5.
+        let position = Position::from_fen("4k3/8/4p3/3p4/8/8/3Q4/4K3 w - - 0 1").unwrap();
+        let result = search(&position, depth(1));
+
+        assert_ne!(result.best_move.to_uci(), "d2d5");
+        assert!(result.score > QUEEN_VALUE - 3 * PAWN_VALUE);
+    }
+
+    #[test]
+    fn test_quiescence_stand_pat() {
+        let mut search = Search::new(SearchLimits::default());
+
+        // Quiet, so the static evaluation stands.
+        let mut position = Position::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            search.quiescence(&mut position, -INFINITY, INFINITY, 0),
+            evaluate(&position)
+        );
+
+        // A hanging rook is taken.
+        let mut position = Position::from_fen("4k3/8/8/8/8/8/4r3/4K3 w - - 0 1").unwrap();
+        let bare_kings = Position::from_fen("4k3/8/8/8/8/8/4K3/8 b - - 0 1").unwrap();
+        assert_eq!(
+            search.quiescence(&mut position, -INFINITY, INFINITY, 0),
+            -evaluate(&bare_kings)
+        );
+        assert_eq!(search.pv_table[0][0].to_uci(), "e1e2");
+
+        // Checkmated: no stand pat, no evasions.
+        let mut position =
+            Position::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+        assert_eq!(
+            search.quiescence(&mut position, -INFINITY, INFINITY, 0),
+            -MATE
+        );
+    }
+
+    #[test]
+    fn test_transposition_table_is_shared() {
+        let position = Position::from_fen(DEFAULT_FEN).unwrap();
+        let tt = Arc::new(TranspositionTable::new(1));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let first = Search::with_table(depth(4), Arc::clone(&stop), Arc::clone(&tt)).run(&position);
+        let root = tt.probe(position.key(), 0).unwrap();
+        assert_eq!(root.mv, first.best_move);
+        assert_eq!(root.depth, 4);
+        assert_eq!(root.bound, Bound::EXACT);
+
+        // The second search finds its answers in the table.
+        let second = Search::with_table(depth(4), stop, tt).run(&position);
+        assert_eq!(second.score, first.score);
+        assert!(second.nodes < first.nodes);
+    }
+
+    #[test]
+    fn test_selective_search() {
+        let position = Position::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+
+        let run = |config: SearchConfig| {
+            let mut search = Search::new(depth(5));
+            search.set_config(config);
+            search.run(&position)
+        };
+
+        let full = run(SearchConfig::NONE);
+        let selective = run(SearchConfig::default());
+        assert!(selective.nodes < full.nodes);
+
+        // Each technique on its own still solves the mates.
+        for config in [
+            SearchConfig {
+                null_move: true,
+                ..SearchConfig::NONE
+            },
+            SearchConfig {
+                late_move_reductions: true,
+                ..SearchConfig::NONE
+            },
+            SearchConfig {
+                reverse_futility: true,
+                ..SearchConfig::NONE
+            },
+            SearchConfig {
+                futility: true,
+                ..SearchConfig::NONE
+            },
+            SearchConfig {
+                late_move_pruning: true,
+                ..SearchConfig::NONE
+            },
+            SearchConfig::default(),
+        ] {
+            let mut search = Search::new(depth(4));
+            search.set_config(config);
+            let position = Position::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+
+            assert_eq!(
+                search.run(&position).score(),
+                Score::MATE(2),
+                "{:?}",
+                config
+            );
+        }
+    }
+
+    #[test]
+    fn test_null_move_needs_pieces() {
+        // In pawn endings zugzwang is common, so passing is not tried there.
+        let pawns = Position::from_fen("8/8/8/4k3/8/3PK3/8/8 w - - 0 1").unwrap();
+        assert!(!has_non_pawn_material(&pawns));
+
+        let knight = Position::from_fen("8/8/8/4k3/8/3PK3/8/6N1 w - - 0 1").unwrap();
+        assert!(has_non_pawn_material(&knight));
+
+        // Only the side to move counts.
+        let knight = Position::from_fen("8/8/8/4k3/8/3PK3/8/6N1 b - - 0 1").unwrap();
+        assert!(!has_non_pawn_material(&knight));
+    }
+
+    #[test]
+    fn test_late_move_reduction() {
+        assert_eq!(late_move_reduction(1, 1), 0);
+        assert_eq!(late_move_reduction(3, 3), 1);
+        assert!(late_move_reduction(20, 40) > late_move_reduction(6, 6));
+        assert_eq!(late_move_reduction(200, 200), late_move_reduction(63, 63));
+    }
+
+    #[test]
+    fn test_searchmoves() {
+        let position = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let king_moves: Vec<Move> = position
+            .legal_moves()
+            .iter()
+            .copied()
+            .filter(|mv| mv.from() == Square::G1)
+            .collect();
+
+        let result = search(
+            &position,
+            SearchLimits {
+                searchmoves: king_moves.clone(),
+                ..depth(3)
+            },
+        );
+        assert!(king_moves.contains(&result.best_move));
+        assert!(matches!(result.score(), Score::CP(_)));
+
+        // Moves that are not legal are dropped, leaving every move.
+        let result = search(
+            &position,
+            SearchLimits {
+                searchmoves: vec![Move::new(Square::E2, Square::E4, Move::DOUBLE_PAWN_PUSH)],
+                ..depth(3)
+            },
+        );
+        assert_eq!(result.score(), Score::MATE(1));
+    }
+
+    #[test]
+    fn test_excluded_moves() {
+        let a1a8 = Move::new(Square::A1, Square::A8, Move::QUIET);
+        let mut search = Search::new(SearchLimits {
+            searchmoves: vec![a1a8],
+            ..SearchLimits::default()
+        });
+
+        assert!(search.has_exclusions(0));
+        assert!(!search.is_excluded(0, a1a8));
+        assert!(search.is_excluded(0, Move::new(Square::G1, Square::F1, Move::QUIET)));
+        assert!(!search.has_exclusions(1));
+
+        search.excluded[1] = a1a8;
+        assert!(search.has_exclusions(1));
+        assert!(search.is_excluded(1, a1a8));
+        assert!(!search.is_excluded(2, a1a8));
+    }
+
+    #[test]
+    fn test_singular_extension_search_keeps_results() {
+        // Deep enough for singular extensions, which must not disturb the result.
+        let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let result = search(&position, depth(9));
+
+        assert_eq!(result.best_move.to_uci(), "d2d5");
+        assert_eq!(result.pv[0], result.best_move);
+    }
+
+    #[test]
+    fn test_no_legal_moves() {
+        let stalemate = Position::from_fen("k7/8/1Q6/8/8/8/8/K7 b - - 0 1").unwrap();
+        let result = search(&stalemate, depth(3));
+
+        assert!(result.best_move.is_null());
+        assert_eq!(result.score, 0);
+    }
+
+    #[test]
+    fn test_limits() {
+        let position = Position::from_fen(DEFAULT_FEN).unwrap();
+
+        let result = search(&position, depth(2));
+        assert_eq!(result.depth, 2);
+        assert_eq!(result.pv.len(), 2);
+
+        let result = search(
+            &position,
+            SearchLimits {
+                nodes: Some(5_000),
+                ..SearchLimits::default()
+            },
+        );
+        assert!(result.nodes <= 5_000);
+        assert!(!result.best_move.is_null());
+
+        let result = search(
+            &position,
+            SearchLimits {
+                time: Some(Duration::from_millis(50)),
+                ..SearchLimits::default()
+            },
+        );
+        // The bound is far above the limit so that a busy machine does not fail the test.
+        assert!(result.elapsed < Duration::from_secs(2));
+        assert!(result.depth >= 1);
+        assert!(position.legal_moves().contains(&result.best_move));
+    }
+
+    #[test]
+    fn test_stop_flag() {
+        let position = Position::from_fen(DEFAULT_FEN).unwrap();
+        let stop = Arc::new(AtomicBool::new(true));
+        let result = Search::with_stop(SearchLimits::default(), stop).run(&position);
+
+        assert_eq!(result.depth, 0);
+        assert!(position.legal_moves().contains(&result.best_move));
+    }
+
+    #[test]
+    fn test_repetition_is_a_draw() {
+        let mut position = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1").unwrap();
+        assert!(!is_draw(&position));
+
+        for uci in ["h1h2", "e8d8", "h2h1", "d8e8"] {
+            let mv = *position
+                .legal_moves()
+                .iter()
+                .find(|mv| mv.to_uci() == uci)
+                .unwrap();
+            position.make_move(mv);
+        }
+
+        assert!(is_draw(&position));
+    }
+}
@@ -0,0 +1,253 @@
+use crate::attack_maps::ray_attacks::{ray, ray_attacks, BISHOP_DIRECTIONS, RAY_DIRECTIONS};
+use crate::board::Board;
+use crate::constants::ZERO;
+use crate::eval::{piece_value, PAWN_VALUE, QUEEN_VALUE};
+use crate::moves::Move;
+use crate::piece::{Color, Piece, Piece::*};
+use crate::position::{en_passant_capture_square, Position};
+use crate::types::bitboard::{bitboard_squares, BitBoard};
+use crate::types::square::Square;
+
+// Longest possible exchange: every piece of both sides takes once.
+const MAX_EXCHANGE: usize = 32;
+
+// The slider behind `vacated` on the line through `target`, now that nothing blocks it.
+fn xray(board: &Board, target: Square, vacated: Square, occupancy: BitBoard) -> BitBoard {
+    let direction = match RAY_DIRECTIONS
+        .iter()
+        .find(|direction| ray(target, **direction) & vacated.as_bitboard() != ZERO)
+    {
+        Some(direction) => *direction,
+        None => return ZERO,
+    };
+
+    let sliders = |color: Color| {
+        let queens = board.piece_bit_board(QUEEN(color));
+
+        if BISHOP_DIRECTIONS.contains(&direction) {
+            board.piece_bit_board(BISHOP(color)) | queens
+        } else {
+            board.piece_bit_board(ROOK(color)) | queens
+        }
+    };
+
+    ray_attacks(target, occupancy, direction)
+        & occupancy
+        & (sliders(Color::WHITE) | sliders(Color::BLACK))
+}
+
+fn least_valuable(board: &Board, attackers: BitBoard, color: Color) -> Option<(Square, Piece)> {
+    [
+        PAWN(color),
+        KNIGHT(color),
+        BISHOP(color),
+        ROOK(color),
+        QUEEN(color),
+        KING(color),
+    ]
+    .iter()
+    .find_map(|piece| {
+        let square = bitboard_squares(board.piece_bit_board(*piece) & attackers).next()?;
+        Some((square, *piece))
+    })
+}
+
+// Material won when a pawn reaching the last rank turns into a queen.
+fn promotion_gain(piece: Piece, to: Square) -> i32 {
+    let last_rank = to as u64 / 8 == 0 || to as u64 / 8 == 7;
+
+    if matches!(piece, PAWN(_)) && last_rank {
+        QUEEN_VALUE - PAWN_VALUE
+    } else {
+        0
+    }
+}
+
+impl Position {
+    // Static exchange evaluation: the material the side to move wins with mv once all captures
+    // on the target square are played out, cheapest attacker first, and either side may stop
+    // capturing whenever that suits it better. Pins are not taken into account.
+    pub fn see(&self, mv: Move) -> i32 {
+        if mv.is_castle() {
+            return 0;
+        }
+
+        let board = self.board();
+        let color = self.side_to_move();
+        let to = mv.to();
+        let mover = match board.piece_on_square(mv.from()) {
+            Some(piece) => piece,
+            None => return 0,
+        };
+
+        let mut occupancy = board.all_bit_boards() ^ mv.from().as_bitboard();
+        let mut gain = [0; MAX_EXCHANGE];
+
+        gain[0] = if mv.is_en_passant() {
+            occupancy ^= en_passant_capture_square(mv).as_bitboard();
+            PAWN_VALUE
+        } else {
+            board.piece_on_square(to).map_or(0, piece_value)
+        };
+
+        // Value of the piece standing on the target square, which the next capture wins.
+        let mut on_square = match mv.promotion(color) {
+            Some(promotion) => {
+                gain[0] += piece_value(promotion) - PAWN_VALUE;
+                piece_value(promotion)
+            }
+            None => piece_value(mover),
+        };
+
+        let mut attackers = board.attackers_to(to, occupancy) & occupancy;
+        let mut side = color.oposite();
+        let mut depth = 0;
+
+        while depth + 1 < MAX_EXCHANGE {
+            let (square, piece) = match least_valuable(board, attackers, side) {
+                Some(attacker) => attacker,
+                None => break,
+            };
+
+            occupancy ^= square.as_bitboard();
+            attackers ^= square.as_bitboard();
+            attackers |= xray(board, to, square, occupancy);
+
+            // The king may only take when nothing can take back.
+            if matches!(piece, KING(_)) && attackers & board.color_bit_board(side.oposite()) != ZERO
+            {
+                break;
+            }
+
+            depth += 1;
+            gain[depth] = on_square + promotion_gain(piece, to) - gain[depth - 1];
+            on_square = piece_value(piece) + promotion_gain(piece, to);
+            side = side.oposite();
+        }
+
+        // Each side picks between taking and standing pat, from the last capture backwards.
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+
+        gain[0]
+    }
+
+    pub fn see_ge(&self, mv: Move, threshold: i32) -> bool {
+        self.see(mv) >= threshold
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::{BISHOP_VALUE, KNIGHT_VALUE, ROOK_VALUE};
+    use crate::types::square::Square::*;
+
+    fn see(fen: &str, uci: &str) -> i32 {
+        let position = Position::from_fen(fen).unwrap();
+        let mv = *position
+            .legal_moves()
+            .iter()
+            .find(|mv| mv.to_uci() == uci)
+            .unwrap();
+
+        position.see(mv)
+    }
+
+    #[test]
+    fn test_undefended_and_defended() {
+        assert_eq!(
+            see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+            PAWN_VALUE
+        );
+        assert_eq!(
+            see(
+                "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+                "d3e5"
+            ),
+            PAWN_VALUE - KNIGHT_VALUE
+        );
+    }
+
+    #[test]
+    fn test_xrays() {
+        // The second rook only joins once the first has left d2.
+        assert_eq!(
+            see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"),
+            PAWN_VALUE
+        );
+        assert_eq!(
+            see("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1", "d2d5"),
+            PAWN_VALUE - ROOK_VALUE
+        );
+
+        // A queen behind a bishop backs it up diagonally.
+        assert_eq!(
+            see("4k3/8/2p5/1p6/8/3B4/4Q3/4K3 w - - 0 1", "d3b5"),
+            PAWN_VALUE - BISHOP_VALUE + PAWN_VALUE
+        );
+        assert_eq!(
+            see("4k3/8/2p5/1p6/8/3B4/8/4K3 w - - 0 1", "d3b5"),
+            PAWN_VALUE - BISHOP_VALUE
+        );
+    }
+
+    #[test]
+    fn test_king_cannot_take_defended_piece() {
+        assert_eq!(
+            see("3rk3/8/2p5/3pK3/8/8/8/3R4 w - - 0 1", "d1d5"),
+            PAWN_VALUE - ROOK_VALUE
+        );
+        assert_eq!(see("3rk3/8/8/3pK3/8/8/8/3R4 w - - 0 1", "d1d5"), PAWN_VALUE);
+    }
+
+    #[test]
+    fn test_promotions() {
+        assert_eq!(
+            see("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"),
+            QUEEN_VALUE - PAWN_VALUE
+        );
+        assert_eq!(
+            see("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n"),
+            KNIGHT_VALUE - PAWN_VALUE
+        );
+        // The new queen is taken at once.
+        assert_eq!(
+            see("1r2k3/2P5/8/8/8/8/8/4K3 w - - 0 1", "c7c8q"),
+            -PAWN_VALUE
+        );
+        // Capturing promotion, recaptured by a pawn that promotes itself.
+        assert_eq!(
+            see("nr2k3/1P6/8/8/8/8/8/1R2K3 w - - 0 1", "b7a8q"),
+            KNIGHT_VALUE + QUEEN_VALUE - PAWN_VALUE - QUEEN_VALUE
+        );
+    }
+
+    #[test]
+    fn test_en_passant() {
+        assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), PAWN_VALUE);
+        assert_eq!(see("4k3/2p5/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 0);
+        // Removing the captured pawn opens the file for the rook behind it.
+        assert_eq!(
+            see("3rk3/8/8/3pP3/8/8/8/3RK3 w - d6 0 1", "e5d6"),
+            PAWN_VALUE
+        );
+    }
+
+    #[test]
+    fn test_quiet_moves() {
+        assert_eq!(see("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a4"), 0);
+        assert_eq!(see("4k3/8/8/1p6/8/8/8/R3K3 w - - 0 1", "a1a4"), -ROOK_VALUE);
+    }
+
+    #[test]
+    fn test_see_ge() {
+        let position = Position::from_fen("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let mv = Move::new(D2, D5, Move::CAPTURE);
+
+        assert!(position.see_ge(mv, PAWN_VALUE - ROOK_VALUE));
+        assert!(!position.see_ge(mv, 0));
+    }
+}
@@ -0,0 +1,361 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::moves::Move;
+use crate::search::MATE_BOUND;
+
+pub const DEFAULT_HASH_MB: usize = 16;
+
+const BUCKET_SIZE: usize = 4;
+const GENERATION_MASK: u8 = 0x3F;
+
+// Slots looked at to estimate how full the table is, as UCI wants it in permille.
+const HASHFULL_SAMPLE: usize = 1000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Bound {
+    EXACT,
+    // The score is at least this much, the search failed high.
+    LOWER,
+    // The score is at most this much, no move raised alpha.
+    UPPER,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TtEntry {
+    pub mv: Move,
+    pub score: i32,
+    pub depth: u8,
+    pub bound: Bound,
+}
+
+impl TtEntry {
+    // Bits 0-15 hold the move, 16-31 the score, 32-39 the depth, 40-41 the bound and
+    // 42-47 the generation. All zero means the slot is empty.
+    fn pack(self, generation: u8) -> u64 {
+        let bound = match self.bound {
+            Bound::EXACT => 1,
+            Bound::LOWER => 2,
+            Bound::UPPER => 3,
+        };
+
+        self.mv.bits() as u64
+            | (self.score as i16 as u16 as u64) << 16
+            | (self.depth as u64) << 32
+            | bound << 40
+            | ((generation & GENERATION_MASK) as u64) << 42
+    }
+
+    fn unpack(data: u64) -> Option<(TtEntry, u8)> {
+        let bound = match (data >> 40) & 3 {
+            1 => Bound::EXACT,
+            2 => Bound::LOWER,
+            3 => Bound::UPPER,
+            _ => return None,
+        };
+
+        let entry = TtEntry {
+            mv: Move::from_bits(data as u16),
+            score: (data >> 16) as u16 as i16 as i32,
+            depth: (data >> 32) as u8,
+            bound,
+        };
+
+        Some((entry, (data >> 42) as u8 & GENERATION_MASK))
+    }
+}
+
+// Mate scores are stored relative to the node, not the root, so they stay right wherever the
+// position turns up again.
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+// The key is stored xor'ed with the data. A slot torn by two threads writing at once no longer
+// matches its key and reads as a miss, which makes the table safe to share without locks.
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Slot {
+    fn load(&self) -> (u64, u64) {
+        let data = self.data.load(Ordering::Relaxed);
+
+        (self.key.load(Ordering::Relaxed) ^ data, data)
+    }
+
+    fn store(&self, key: u64, data: u64) {
+        self.key.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+#[repr(align(64))]
+struct Bucket {
+    slots: [Slot; BUCKET_SIZE],
+}
+
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    generation: AtomicU8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl TranspositionTable {
+    pub fn new(megabytes: usize) -> TranspositionTable {
+        let count = (megabytes * 1024 * 1024 / std::mem::size_of::<Bucket>()).max(1);
+
+        TranspositionTable {
+            buckets: (0..count).map(|_| Bucket::default()).collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    pub fn clear(&self) {
+        for slot in self.buckets.iter().flat_map(|bucket| bucket.slots.iter()) {
+            slot.store(0, 0);
+        }
+
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    // Entries from earlier searches are the first to be replaced.
+    pub fn new_search(&self) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.generation.store(
+            generation.wrapping_add(1) & GENERATION_MASK,
+            Ordering::Relaxed,
+        );
+    }
+
+    fn generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    fn bucket(&self, key: u64) -> &Bucket {
+        // Maps the key onto the table without requiring a power of two size.
+        let index = (key as u128 * self.buckets.len() as u128) >> 64;
+        &self.buckets[index as usize]
+    }
+
+    pub fn probe(&self, key: u64, ply: usize) -> Option<TtEntry> {
+        self.bucket(key).slots.iter().find_map(|slot| {
+            let (slot_key, data) = slot.load();
+            let (mut entry, _) = TtEntry::unpack(data).filter(|_| slot_key == key)?;
+            entry.score = score_from_tt(entry.score, ply);
+
+            Some(entry)
+        })
+    }
+
+    pub fn store(&self, key: u64, mut entry: TtEntry, ply: usize) {
+        let generation = self.generation();
+        let slots = &self.bucket(key).slots;
+
+        // Prefer the slot of the same position, then an empty one, then the one holding the
+        // least work: shallow entries left over from older searches.
+        let same = slots.iter().find(|slot| slot.load().0 == key);
+        let slot = same.unwrap_or_else(|| {
+            slots
+                .iter()
+                .min_by_key(|slot| match TtEntry::unpack(slot.load().1) {
+                    Some((old, old_generation)) => {
+                        let age = generation.wrapping_sub(old_generation) & GENERATION_MASK;
+                        old.depth as i32 - 8 * age as i32
+                    }
+                    None => i32::MIN,
+                })
+                .unwrap()
+        });
+
+        if let (Some(_), Some((old, _))) = (same, TtEntry::unpack(slot.load().1)) {
+            if entry.mv.is_null() {
+                entry.mv = old.mv;
+            }
+        }
+
+        entry.score = score_to_tt(entry.score, ply);
+        slot.store(key, entry.pack(generation));
+    }
+
+    // Permille of the sampled slots used by the current search.
+    pub fn hashfull(&self) -> u32 {
+        let generation = self.generation();
+        let slots = self
+            .buckets
+            .iter()
+            .flat_map(|bucket| bucket.slots.iter())
+            .take(HASHFULL_SAMPLE);
+
+        let (mut used, mut sampled) = (0, 0);
+        for slot in slots {
+            sampled += 1;
+
+            if let Some((_, entry_generation)) = TtEntry::unpack(slot.load().1) {
+                used += (entry_generation == generation) as u32;
+            }
+        }
+
+        used * 1000 / sampled.max(1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::search::MATE;
+    use crate::types::square::Square::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn entry(score: i32, depth: u8) -> TtEntry {
+        TtEntry {
+            mv: Move::new(E2, E4, Move::DOUBLE_PAWN_PUSH),
+            score,
+            depth,
+            bound: Bound::LOWER,
+        }
+    }
+
+    #[test]
+    fn test_store_and_probe() {
+        let tt = TranspositionTable::new(1);
+
+        assert_eq!(tt.probe(42, 0), None);
+
+        tt.store(42, entry(-1234, 7), 0);
+        assert_eq!(tt.probe(42, 0), Some(entry(-1234, 7)));
+        assert_eq!(tt.probe(43, 0), None);
+
+        // Storing again without a move keeps the old one.
+        let update = TtEntry {
+            mv: Move::NULL,
+            bound: Bound::EXACT,
+            ..entry(5, 9)
+        };
+        tt.store(42, update, 0);
+        assert_eq!(
+            tt.probe(42, 0),
+            Some(TtEntry {
+                bound: Bound::EXACT,
+                ..entry(5, 9)
+            })
+        );
+
+        tt.clear();
+        assert_eq!(tt.probe(42, 0), None);
+    }
+
+    #[test]
+    fn test_mate_scores_are_ply_relative() {
+        let tt = TranspositionTable::new(1);
+
+        // Mate in 5 plies from the root, found at ply 3: 2 plies from the node.
+        tt.store(1, entry(MATE - 5, 4), 3);
+        assert_eq!(tt.probe(1, 3).unwrap().score, MATE - 5);
+        assert_eq!(tt.probe(1, 1).unwrap().score, MATE - 3);
+
+        tt.store(2, entry(-MATE + 6, 4), 4);
+        assert_eq!(tt.probe(2, 0).unwrap().score, -MATE + 2);
+
+        tt.store(3, entry(250, 4), 10);
+        assert_eq!(tt.probe(3, 0).unwrap().score, 250);
+    }
+
+    #[test]
+    fn test_replacement_prefers_old_and_shallow_entries() {
+        // A table of one bucket makes every key collide.
+        let tt = TranspositionTable::new(0);
+        assert_eq!(tt.buckets.len(), 1);
+
+        for key in 1..=4 {
+            tt.store(key, entry(0, 10 + key as u8), 0);
+        }
+
+        tt.store(5, entry(0, 1), 0);
+        assert_eq!(tt.probe(1, 0), None);
+        assert!(tt.probe(2, 0).is_some());
+
+        // After a few searches deep entries give way to shallow fresh ones.
+        for _ in 0..3 {
+            tt.new_search();
+        }
+        for key in 6..=8 {
+            tt.store(key, entry(0, 1), 0);
+        }
+        assert_eq!(tt.probe(5, 0), None);
+        assert_eq!(tt.probe(2, 0), None);
+        assert_eq!(tt.probe(3, 0), None);
+        assert!(tt.probe(4, 0).is_some());
+    }
+
+    #[test]
+    fn test_hashfull() {
+        let tt = TranspositionTable::new(1);
+        assert_eq!(tt.hashfull(), 0);
+
+        // Keys spread evenly over the table so that a quarter of the sampled slots fill up.
+        let buckets = tt.buckets.len() as u64;
+        for i in 0..(HASHFULL_SAMPLE / BUCKET_SIZE) as u64 {
+            tt.store((i * (u64::MAX / buckets)) + 1, entry(0, 1), 0);
+        }
+        assert_eq!(tt.hashfull(), 250);
+
+        tt.new_search();
+        assert_eq!(tt.hashfull(), 0);
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let tt = Arc::new(TranspositionTable::new(0));
+
+        // All threads write the same keys, each with its own depths.
+        let writers: Vec<_> = (0..4u8)
+            .map(|t| {
+                let tt = Arc::clone(&tt);
+                thread::spawn(move || {
+                    for i in 0..10_000u64 {
+                        let depth = t * 50 + (i % 50) as u8;
+                        tt.store(i % 8 + 1, entry(depth as i32 * 3, depth), 0);
+                    }
+                })
+            })
+            .collect();
+
+        for _ in 0..10_000 {
+            for key in 1..=8 {
+                // A hit never mixes the data of two writes.
+                if let Some(hit) = tt.probe(key, 0) {
+                    assert_eq!(hit.score, hit.depth as i32 * 3);
+                }
+            }
+        }
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+    }
+}
@@ -10,6 +10,21 @@ pub fn square_occupied(bboard: BitBoard, square: Square) -> bool {
     bboard & (ONE << square as u64) != 0
 }
 
+pub fn pop_lsb(bitboard: &mut BitBoard) -> Option<Square> {
+    if *bitboard == ZERO {
+        return None;
+    }
+
+    let square = Square::try_from(bitboard.trailing_zeros() as u64).unwrap();
+    *bitboard &= *bitboard - 1;
+
+    Some(square)
+}
+
+pub fn bitboard_squares(mut bitboard: BitBoard) -> impl Iterator<Item = Square> {
+    std::iter::from_fn(move || pop_lsb(&mut bitboard))
+}
+
 pub const fn north_one(bitboard: BitBoard) -> BitBoard {
     bitboard << 8
 }
@@ -65,7 +80,7 @@ fn flatten_multiline_string_to_bitboard_repr(s: String) -> Result<Vec<char>, &'s
             .chunks(RANK_COUNT as usize)
             .rev()
             .flat_map(|chunk| chunk.iter())
-            .map(|c| *c)
+            .copied()
             .collect())
     }
 }
@@ -83,7 +98,7 @@ pub fn bitboard_to_str(bitboard: BitBoard) -> String {
         .chunks(RANK_COUNT as usize)
         .rev()
         .flat_map(|chunk| chunk.iter().chain(['\n'].iter()))
-        .map(|c| *c)
+        .copied()
         .collect()
 }
 
@@ -102,7 +117,7 @@ mod test {
                 '.', '.', '.', '.', '.', '.', '.', '.', '.', '.', '.', '.', '.', '.', '.', '.',
             ]
             .iter()
-            .map(|c| *c),
+            .copied(),
         );
 
         assert_eq!(
@@ -205,6 +220,16 @@ mod test {
         assert_eq!(correct, to_test)
     }
 
+    #[test]
+    fn test_bitboard_squares() {
+        let bitboard =
+            Square::B2.as_bitboard() | Square::A1.as_bitboard() | Square::H8.as_bitboard();
+        let squares: Vec<Square> = bitboard_squares(bitboard).collect();
+
+        assert_eq!(squares, vec![Square::A1, Square::B2, Square::H8]);
+        assert_eq!(bitboard_squares(ZERO).count(), 0);
+    }
+
     #[test]
     fn test_south_one() {
         assert_eq!(south_one(TWO_RANK), ONE_RANK);
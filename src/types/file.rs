@@ -65,6 +65,20 @@ impl File {
         }
     }
 
+    pub fn from_char(c: char) -> Option<File> {
+        match c {
+            'a' => Some(File::A),
+            'b' => Some(File::B),
+            'c' => Some(File::C),
+            'd' => Some(File::D),
+            'e' => Some(File::E),
+            'f' => Some(File::F),
+            'g' => Some(File::G),
+            'h' => Some(File::H),
+            _ => None,
+        }
+    }
+
     pub const fn mask(self) -> BitBoard {
         match self {
             File::A => A_FILE,
@@ -55,6 +55,33 @@ impl Rank {
         }
     }
 
+    pub fn to_char(self) -> char {
+        match self {
+            Rank::ONE => '1',
+            Rank::TWO => '2',
+            Rank::THREE => '3',
+            Rank::FOUR => '4',
+            Rank::FIVE => '5',
+            Rank::SIX => '6',
+            Rank::SEVEN => '7',
+            Rank::EIGHT => '8',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Rank> {
+        match c {
+            '1' => Some(Rank::ONE),
+            '2' => Some(Rank::TWO),
+            '3' => Some(Rank::THREE),
+            '4' => Some(Rank::FOUR),
+            '5' => Some(Rank::FIVE),
+            '6' => Some(Rank::SIX),
+            '7' => Some(Rank::SEVEN),
+            '8' => Some(Rank::EIGHT),
+            _ => None,
+        }
+    }
+
     pub const fn mask(self) -> BitBoard {
         match self {
             Rank::ONE => ONE_RANK,
@@ -68,11 +95,37 @@ impl Rank {
         }
     }
 
+    // Every square on this rank and the ranks below it.
     pub const fn mask_till(self) -> BitBoard {
-        FULL_BOARD >> (64 - self as u64)
+        FULL_BOARD >> (8 * (7 - self as u64))
     }
 
+    // Every square on this rank and the ranks above it.
     pub const fn mask_from(self) -> BitBoard {
-        FULL_BOARD << 1
+        FULL_BOARD << (8 * self as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constants::{FIVE_RANK, FOUR_RANK, SIX_RANK};
+
+    #[test]
+    fn test_mask_till() {
+        assert_eq!(Rank::ONE.mask_till(), ONE_RANK);
+        assert_eq!(Rank::TWO.mask_till(), ONE_RANK | TWO_RANK);
+        assert_eq!(Rank::EIGHT.mask_till(), FULL_BOARD);
+    }
+
+    #[test]
+    fn test_mask_from() {
+        assert_eq!(Rank::ONE.mask_from(), FULL_BOARD);
+        assert_eq!(Rank::SEVEN.mask_from(), SEVEN_RANK | EIGHT_RANK);
+        assert_eq!(Rank::EIGHT.mask_from(), EIGHT_RANK);
+        assert_eq!(
+            Rank::FOUR.mask_from() & Rank::SIX.mask_till(),
+            FOUR_RANK | FIVE_RANK | SIX_RANK
+        );
     }
 }
@@ -1,5 +1,8 @@
+use crate::constants::SQUARE_COUNT;
 use int_enum::IntEnum;
+use std::fmt;
 use std::hash::Hash;
+use std::str::FromStr;
 use strum_macros::EnumIter;
 
 #[repr(u64)]
@@ -73,6 +76,13 @@ pub enum Square {
 
 use Square::*;
 
+// Every square in index order, usable where Square::try_from is not, e.g. in const fns.
+pub const SQUARES: [Square; SQUARE_COUNT as usize] = [
+    A1, B1, C1, D1, E1, F1, G1, H1, A2, B2, C2, D2, E2, F2, G2, H2, A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4, A5, B5, C5, D5, E5, F5, G5, H5, A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7, A8, B8, C8, D8, E8, F8, G8, H8,
+];
+
 impl TryFrom<u64> for Square {
     type Error = &'static str;
 
@@ -162,6 +172,37 @@ impl Square {
     pub const fn as_bitboard(self) -> u64 {
         1 << (self as u64)
     }
+
+    pub fn from_file_rank(file: File, rank: Rank) -> Square {
+        Square::try_from(rank as u64 * 8 + file as u64).unwrap()
+    }
+}
+
+impl FromStr for Square {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None) => Ok(Square::from_file_rank(
+                File::from_char(file).ok_or("Cannot convert given string to Square")?,
+                Rank::from_char(rank).ok_or("Cannot convert given string to Square")?,
+            )),
+            _ => Err("Cannot convert given string to Square"),
+        }
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.file().unwrap().to_char(),
+            self.rank().unwrap().to_char()
+        )
+    }
 }
 
 #[cfg(test)]
@@ -177,4 +218,19 @@ mod tests {
     fn test_square_2_file() {
         assert_eq!(Square::C4.file(), Some(File::C));
     }
+
+    #[test]
+    fn test_square_from_str() {
+        assert_eq!("e4".parse::<Square>(), Ok(Square::E4));
+        assert_eq!("h8".parse::<Square>(), Ok(Square::H8));
+        assert!("i1".parse::<Square>().is_err());
+        assert!("e9".parse::<Square>().is_err());
+        assert!("e44".parse::<Square>().is_err());
+    }
+
+    #[test]
+    fn test_square_to_string() {
+        assert_eq!(Square::A1.to_string(), "a1");
+        assert_eq!(Square::F7.to_string(), "f7");
+    }
 }
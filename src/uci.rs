@@ -0,0 +1,781 @@
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::board::board_to_str;
+use crate::constants::DEFAULT_FEN;
+use crate::moves::Move;
+use crate::piece::Color;
+use crate::polyglot::Book;
+use crate::position::Position;
+use crate::search::{Search, SearchConfig, SearchLimits, SearchResult};
+use crate::tt::{TranspositionTable, DEFAULT_HASH_MB};
+
+pub const ENGINE_NAME: &str = "ruch";
+pub const ENGINE_AUTHOR: &str = "the ruch developers";
+
+pub const MAX_HASH_MB: usize = 65536;
+
+// Switches for the selective search, see SearchConfig.
+const SEARCH_OPTIONS: [&str; 5] = [
+    "NullMove",
+    "LateMoveReductions",
+    "ReverseFutility",
+    "Futility",
+    "LateMovePruning",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GoParams {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<u64>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u32>,
+    pub infinite: bool,
+    pub searchmoves: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciCommand {
+    Uci,
+    IsReady,
+    UciNewGame,
+    Position { fen: String, moves: Vec<String> },
+    Go(GoParams),
+    Stop,
+    Quit,
+    SetOption { name: String, value: Option<String> },
+    Display,
+}
+
+fn parse_position(tokens: &[&str]) -> Option<UciCommand> {
+    let moves_at = tokens.iter().position(|t| *t == "moves");
+    let (setup, moves) = match moves_at {
+        Some(i) => (&tokens[..i], &tokens[i + 1..]),
+        None => (tokens, &[][..]),
+    };
+
+    let fen = match setup.split_first() {
+        Some((&"startpos", [])) => DEFAULT_FEN.to_string(),
+        Some((&"fen", fen)) if !fen.is_empty() => fen.join(" "),
+        _ => return None,
+    };
+
+    Some(UciCommand::Position {
+        fen,
+        moves: moves.iter().map(|m| m.to_string()).collect(),
+    })
+}
+
+fn parse_go(tokens: &[&str]) -> GoParams {
+    // Values that fail to parse are left unset rather than rejecting the whole command.
+    let mut params = GoParams::default();
+    let mut tokens = tokens.iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        if *token == "searchmoves" {
+            // Moves run up to the next keyword, which all consist of letters only.
+            while let Some(mv) = tokens.next_if(|t| t.chars().any(|c| c.is_ascii_digit())) {
+                params.searchmoves.push(mv.to_string());
+            }
+            continue;
+        }
+
+        let mut value = || tokens.next().and_then(|v| v.parse::<u64>().ok());
+
+        match *token {
+            "depth" => params.depth = value().map(|v| v as u32),
+            "nodes" => params.nodes = value(),
+            "movetime" => params.movetime = value(),
+            "wtime" => params.wtime = value(),
+            "btime" => params.btime = value(),
+            "winc" => params.winc = value(),
+            "binc" => params.binc = value(),
+            "movestogo" => params.movestogo = value().map(|v| v as u32),
+            "infinite" => params.infinite = true,
+            _ => {}
+        }
+    }
+
+    params
+}
+
+fn parse_setoption(tokens: &[&str]) -> Option<UciCommand> {
+    // Option names and values may contain spaces: setoption name <id> [value <x>]
+    let (&"name", rest) = tokens.split_first()? else {
+        return None;
+    };
+
+    let value_at = rest.iter().position(|t| *t == "value");
+    let (name, value) = match value_at {
+        Some(i) => (&rest[..i], Some(rest[i + 1..].join(" "))),
+        None => (rest, None),
+    };
+
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(UciCommand::SetOption {
+        name: name.join(" "),
+        value,
+    })
+}
+
+pub fn parse_command(line: &str) -> Option<UciCommand> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let (command, args) = tokens.split_first()?;
+
+    match *command {
+        "uci" => Some(UciCommand::Uci),
+        "isready" => Some(UciCommand::IsReady),
+        "ucinewgame" => Some(UciCommand::UciNewGame),
+        "position" => parse_position(args),
+        "go" => Some(UciCommand::Go(parse_go(args))),
+        "stop" => Some(UciCommand::Stop),
+        "quit" => Some(UciCommand::Quit),
+        "setoption" => parse_setoption(args),
+        "d" => Some(UciCommand::Display),
+        _ => None,
+    }
+}
+
+pub fn parse_move(position: &Position, uci: &str) -> Option<Move> {
+    position
+        .legal_moves()
+        .iter()
+        .copied()
+        .find(|mv| mv.to_uci() == uci)
+}
+
+fn parse_check(value: &str) -> bool {
+    value.eq_ignore_ascii_case("true")
+}
+
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+// Time kept back for the GUI and the transport, in milliseconds.
+const MOVE_OVERHEAD: u64 = 50;
+
+// Moves the remaining clock time is spread over when the GUI does not say.
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+pub fn search_limits(params: &GoParams, side: Color) -> SearchLimits {
+    let (time, inc) = match side {
+        Color::WHITE => (params.wtime, params.winc),
+        Color::BLACK => (params.btime, params.binc),
+    };
+
+    let time = match (params.movetime, time) {
+        _ if params.infinite => None,
+        (Some(movetime), _) => Some(movetime),
+        (None, Some(time)) => {
+            let moves_to_go = params
+                .movestogo
+                .map_or(DEFAULT_MOVES_TO_GO, u64::from)
+                .max(1);
+            let budget = time / moves_to_go + inc.unwrap_or(0) * 3 / 4;
+
+            Some(budget.min(time.saturating_sub(MOVE_OVERHEAD)))
+        }
+        (None, None) => None,
+    };
+
+    SearchLimits {
+        depth: params.depth,
+        nodes: params.nodes,
+        time: time.map(Duration::from_millis),
+        ..SearchLimits::default()
+    }
+}
+
+pub fn format_info(result: &SearchResult) -> String {
+    let millis = result.elapsed.as_millis() as u64;
+    let pv: Vec<String> = result.pv.iter().map(|mv| mv.to_uci()).collect();
+
+    format!(
+        "info depth {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+        result.depth,
+        result.score(),
+        result.nodes,
+        result.nodes * 1000 / millis.max(1),
+        result.hashfull,
+        millis,
+        pv.join(" ")
+    )
+}
+
+fn think<F>(
+    position: &Position,
+    params: &GoParams,
+    book: Option<&Book>,
+    stop: &Arc<AtomicBool>,
+    tt: &Arc<TranspositionTable>,
+    config: SearchConfig,
+    on_iteration: F,
+) -> Move
+where
+    F: FnMut(&SearchResult),
+{
+    let mut limits = search_limits(params, position.side_to_move());
+    limits.searchmoves = params
+        .searchmoves
+        .iter()
+        .filter_map(|uci| parse_move(position, uci))
+        .collect();
+
+    // A book move could lie outside searchmoves, so the book only answers unrestricted searches.
+    let book_move = if limits.searchmoves.is_empty() {
+        book.and_then(|book| book.weighted_move(position, random_seed()))
+    } else {
+        None
+    };
+
+    let best = match book_move {
+        Some(mv) => mv,
+        None => {
+            let mut search = Search::with_table(limits, Arc::clone(stop), Arc::clone(tt));
+            search.set_config(config);
+            search.run_with(position, on_iteration).best_move
+        }
+    };
+
+    // In infinite mode bestmove may only be sent once the GUI says stop, stop_search unparks us.
+    while params.infinite && !stop.load(Ordering::Relaxed) {
+        thread::park();
+    }
+
+    best
+}
+
+pub struct Uci<W: Write + Send + 'static> {
+    position: Position,
+    output: Arc<Mutex<W>>,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+    own_book: bool,
+    book: Option<Arc<Book>>,
+    tt: Arc<TranspositionTable>,
+    config: SearchConfig,
+}
+
+impl<W: Write + Send + 'static> Uci<W> {
+    pub fn new(output: W) -> Uci<W> {
+        Uci {
+            position: Position::from_fen(DEFAULT_FEN).expect("DEFAULT_FEN is a valid FEN"),
+            output: Arc::new(Mutex::new(output)),
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+            own_book: false,
+            book: None,
+            tt: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            config: SearchConfig::default(),
+        }
+    }
+
+    fn send(&self, line: &str) {
+        // A closed output means the GUI is gone, there is nobody left to report to.
+        let mut output = self.output.lock().unwrap();
+        let _ = writeln!(output, "{}", line);
+        let _ = output.flush();
+    }
+
+    fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            search.thread().unpark();
+            let _ = search.join();
+        }
+    }
+
+    fn set_position(&mut self, fen: &str, moves: &[String]) {
+        let mut position = match Position::from_fen(fen) {
+            Ok(position) => position,
+            Err(e) => return self.send(&format!("info string invalid FEN: {}", e)),
+        };
+
+        for uci in moves {
+            match parse_move(&position, uci) {
+                Some(mv) => position.make_move(mv),
+                None => return self.send(&format!("info string illegal move {}", uci)),
+            }
+        }
+
+        self.position = position;
+    }
+
+    fn set_option(&mut self, name: &str, value: Option<&str>) {
+        match (name.to_lowercase().as_str(), value) {
+            ("ownbook", Some(value)) => self.own_book = parse_check(value),
+            ("hash", Some(value)) => match value.parse::<usize>() {
+                Ok(megabytes) if (1..=MAX_HASH_MB).contains(&megabytes) => {
+                    self.tt = Arc::new(TranspositionTable::new(megabytes))
+                }
+                _ => self.send(&format!("info string invalid hash size {}", value)),
+            },
+            ("clear hash", None) => self.tt.clear(),
+            ("nullmove", Some(value)) => self.config.null_move = parse_check(value),
+            ("latemovereductions", Some(value)) => {
+                self.config.late_move_reductions = parse_check(value)
+            }
+            ("reversefutility", Some(value)) => self.config.reverse_futility = parse_check(value),
+            ("futility", Some(value)) => self.config.futility = parse_check(value),
+            ("latemovepruning", Some(value)) => self.config.late_move_pruning = parse_check(value),
+            ("bookfile", Some(path)) => match Book::open(path) {
+                Ok(book) => self.book = Some(Arc::new(book)),
+                Err(e) => self.send(&format!("info string cannot open book {}: {}", path, e)),
+            },
+            _ => self.send(&format!("info string unknown option {}", name)),
+        }
+    }
+
+    fn go(&mut self, params: GoParams) {
+        self.stop_search();
+        self.stop.store(false, Ordering::Relaxed);
+
+        let position = self.position.clone();
+        let output = Arc::clone(&self.output);
+        let stop = Arc::clone(&self.stop);
+        let book = self.book.clone().filter(|_| self.own_book);
+        let tt = Arc::clone(&self.tt);
+        let config = self.config;
+
+        self.search = Some(thread::spawn(move || {
+            let best = think(
+                &position,
+                &params,
+                book.as_deref(),
+                &stop,
+                &tt,
+                config,
+                |result| {
+                    let mut output = output.lock().unwrap();
+                    let _ = writeln!(output, "{}", format_info(result));
+                    let _ = output.flush();
+                },
+            );
+
+            let mut output = output.lock().unwrap();
+            let _ = writeln!(output, "bestmove {}", best);
+            let _ = output.flush();
+        }));
+    }
+
+    // Returns false once the engine should exit.
+    pub fn handle(&mut self, line: &str) -> bool {
+        match parse_command(line) {
+            Some(UciCommand::Uci) => {
+                self.send(&format!(
+                    "id name {} {}",
+                    ENGINE_NAME,
+                    env!("CARGO_PKG_VERSION")
+                ));
+                self.send(&format!("id author {}", ENGINE_AUTHOR));
+                self.send(&format!(
+                    "option name Hash type spin default {} min 1 max {}",
+                    DEFAULT_HASH_MB, MAX_HASH_MB
+                ));
+                self.send("option name Clear Hash type button");
+                for name in SEARCH_OPTIONS {
+                    self.send(&format!("option name {} type check default true", name));
+                }
+                self.send("option name OwnBook type check default false");
+                self.send("option name BookFile type string default <empty>");
+                self.send("uciok");
+            }
+            Some(UciCommand::IsReady) => self.send("readyok"),
+            Some(UciCommand::UciNewGame) => {
+                self.stop_search();
+                self.position = Position::from_fen(DEFAULT_FEN).unwrap();
+                self.tt.clear();
+            }
+            Some(UciCommand::Position { fen, moves }) => {
+                self.stop_search();
+                self.set_position(&fen, &moves);
+            }
+            Some(UciCommand::Go(params)) => self.go(params),
+            Some(UciCommand::Stop) => self.stop_search(),
+            Some(UciCommand::Quit) => {
+                self.stop_search();
+                return false;
+            }
+            Some(UciCommand::SetOption { name, value }) => self.set_option(&name, value.as_deref()),
+            Some(UciCommand::Display) => {
+                self.send(&board_to_str(self.position.board()));
+                self.send(&format!("Fen: {}", self.position.to_fen()));
+            }
+            None => {}
+        }
+
+        true
+    }
+
+    pub fn run<R: BufRead>(&mut self, input: R) {
+        for line in input.lines() {
+            match line {
+                Ok(line) if self.handle(&line) => {}
+                _ => break,
+            }
+        }
+
+        self.stop_search();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(str::to_string)
+                .collect()
+        }
+    }
+
+    fn session(commands: &[&str]) -> Vec<String> {
+        let buffer = SharedBuffer::default();
+        let mut uci = Uci::new(buffer.clone());
+
+        uci.run(commands.join("\n").as_bytes());
+
+        buffer.lines()
+    }
+
+    #[test]
+    fn test_parse_position() {
+        assert_eq!(
+            parse_command("position startpos moves e2e4 e7e5"),
+            Some(UciCommand::Position {
+                fen: DEFAULT_FEN.to_string(),
+                moves: vec!["e2e4".to_string(), "e7e5".to_string()],
+            })
+        );
+        assert_eq!(
+            parse_command("position fen 8/8/8/8/8/8/8/K6k w - - 0 1"),
+            Some(UciCommand::Position {
+                fen: "8/8/8/8/8/8/8/K6k w - - 0 1".to_string(),
+                moves: vec![],
+            })
+        );
+        assert_eq!(parse_command("position"), None);
+        assert_eq!(parse_command("position fen"), None);
+    }
+
+    #[test]
+    fn test_parse_go() {
+        assert_eq!(
+            parse_command("go wtime 1000 btime 2000 winc 10 binc 20 movestogo 30"),
+            Some(UciCommand::Go(GoParams {
+                wtime: Some(1000),
+                btime: Some(2000),
+                winc: Some(10),
+                binc: Some(20),
+                movestogo: Some(30),
+                ..GoParams::default()
+            }))
+        );
+        assert_eq!(
+            parse_command("go depth 6 nodes 5000 movetime 250 infinite"),
+            Some(UciCommand::Go(GoParams {
+                depth: Some(6),
+                nodes: Some(5000),
+                movetime: Some(250),
+                infinite: true,
+                ..GoParams::default()
+            }))
+        );
+        assert_eq!(
+            parse_command("go searchmoves e2e4 a7a8q depth 3"),
+            Some(UciCommand::Go(GoParams {
+                depth: Some(3),
+                searchmoves: vec!["e2e4".to_string(), "a7a8q".to_string()],
+                ..GoParams::default()
+            }))
+        );
+        assert_eq!(
+            parse_command("go depth x"),
+            Some(UciCommand::Go(GoParams::default()))
+        );
+    }
+
+    #[test]
+    fn test_parse_setoption() {
+        assert_eq!(
+            parse_command("setoption name Book File value /tmp/my book.bin"),
+            Some(UciCommand::SetOption {
+                name: "Book File".to_string(),
+                value: Some("/tmp/my book.bin".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_command("setoption name Clear Hash"),
+            Some(UciCommand::SetOption {
+                name: "Clear Hash".to_string(),
+                value: None,
+            })
+        );
+        assert_eq!(parse_command("setoption value 1"), None);
+    }
+
+    #[test]
+    fn test_unknown_commands_are_ignored() {
+        assert_eq!(parse_command(""), None);
+        assert_eq!(parse_command("xyzzy 1 2 3"), None);
+
+        let lines = session(&[
+            "xyzzy",
+            "position fen garbage",
+            "position startpos moves e2e5",
+            "isready",
+        ]);
+
+        assert_eq!(lines.last().map(String::as_str), Some("readyok"));
+    }
+
+    #[test]
+    fn test_handshake() {
+        let lines = session(&["uci", "isready", "quit"]);
+
+        assert!(lines[0].starts_with("id name ruch"));
+        assert!(lines.contains(&"uciok".to_string()));
+        assert_eq!(lines.last().map(String::as_str), Some("readyok"));
+    }
+
+    #[test]
+    fn test_display() {
+        let lines = session(&["position startpos moves e2e4", "d"]);
+
+        assert!(lines.contains(&"8 |r|n|b|q|k|b|n|r|".to_string()));
+        assert!(lines.contains(&"4 | | | | |P| | | |".to_string()));
+        assert_eq!(
+            lines.last().map(String::as_str),
+            Some("Fen: rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+        );
+    }
+
+    #[test]
+    fn test_go_returns_legal_move() {
+        let lines = session(&["position startpos moves e2e4 e7e5 g1f3", "go depth 1"]);
+        let best = lines.last().unwrap().strip_prefix("bestmove ").unwrap();
+
+        let mut position = Position::from_fen(DEFAULT_FEN).unwrap();
+        for uci in ["e2e4", "e7e5", "g1f3"] {
+            let mv = parse_move(&position, uci).unwrap();
+            position.make_move(mv);
+        }
+
+        assert!(parse_move(&position, best).is_some());
+    }
+
+    #[test]
+    fn test_go_infinite_waits_for_stop() {
+        let lines = session(&["position startpos", "go infinite", "stop"]);
+        let (best, info) = lines.split_last().unwrap();
+
+        assert!(best.starts_with("bestmove "));
+        assert!(info.iter().all(|line| line.starts_with("info depth ")));
+    }
+
+    #[test]
+    fn test_search_limits() {
+        let params = GoParams {
+            wtime: Some(60_000),
+            btime: Some(30_000),
+            winc: Some(1_000),
+            movestogo: Some(20),
+            ..GoParams::default()
+        };
+
+        assert_eq!(
+            search_limits(&params, Color::WHITE).time,
+            Some(Duration::from_millis(3_750))
+        );
+        assert_eq!(
+            search_limits(&params, Color::BLACK).time,
+            Some(Duration::from_millis(1_500))
+        );
+
+        // Never plan on more time than is left on the clock.
+        let params = GoParams {
+            wtime: Some(40),
+            winc: Some(2_000),
+            ..GoParams::default()
+        };
+        assert_eq!(
+            search_limits(&params, Color::WHITE).time,
+            Some(Duration::ZERO)
+        );
+
+        let params = GoParams {
+            depth: Some(5),
+            movetime: Some(100),
+            wtime: Some(60_000),
+            ..GoParams::default()
+        };
+        assert_eq!(
+            search_limits(&params, Color::WHITE),
+            SearchLimits {
+                depth: Some(5),
+                nodes: None,
+                time: Some(Duration::from_millis(100)),
+                searchmoves: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_go_reports_search_info() {
+        let buffer = SharedBuffer::default();
+        let mut uci = Uci::new(buffer.clone());
+
+        uci.handle("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        uci.handle("go depth 2");
+        uci.search.take().unwrap().join().unwrap();
+
+        let lines = buffer.lines();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("info depth 1 score mate 1 nodes "));
+        assert!(lines[1].starts_with("info depth 2 score mate 1 nodes "));
+        assert!(lines[1].ends_with(" pv a1a8"));
+        assert_eq!(lines[2], "bestmove a1a8");
+    }
+
+    #[test]
+    fn test_hash_option() {
+        let buffer = SharedBuffer::default();
+        let mut uci = Uci::new(buffer.clone());
+
+        uci.handle("setoption name Hash value 1");
+        assert_eq!(uci.tt.hashfull(), 0);
+
+        uci.handle("go depth 3");
+        uci.search.take().unwrap().join().unwrap();
+        let position = Position::from_fen(DEFAULT_FEN).unwrap();
+        assert!(uci.tt.probe(position.key(), 0).is_some());
+
+        uci.handle("setoption name Clear Hash");
+        assert!(uci.tt.probe(position.key(), 0).is_none());
+
+        uci.handle("setoption name Hash value 0");
+        assert_eq!(
+            buffer.lines().last().map(String::as_str),
+            Some("info string invalid hash size 0")
+        );
+    }
+
+    #[test]
+    fn test_go_searchmoves() {
+        let buffer = SharedBuffer::default();
+        let mut uci = Uci::new(buffer.clone());
+
+        uci.handle("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        uci.handle("go depth 3 searchmoves g1f2 g1h2");
+        uci.search.take().unwrap().join().unwrap();
+
+        let best = buffer.lines().last().unwrap().clone();
+        assert!(
+            best == "bestmove g1f2" || best == "bestmove g1h2",
+            "{}",
+            best
+        );
+    }
+
+    #[test]
+    fn test_search_options() {
+        let mut uci = Uci::new(SharedBuffer::default());
+        assert_eq!(uci.config, SearchConfig::default());
+
+        for name in SEARCH_OPTIONS {
+            uci.handle(&format!("setoption name {} value false", name));
+        }
+        assert_eq!(uci.config, SearchConfig::NONE);
+
+        uci.handle("setoption name NullMove value true");
+        assert!(uci.config.null_move);
+    }
+
+    #[test]
+    fn test_go_uses_own_book() {
+        let path = std::env::temp_dir().join(format!("ruch-uci-book-{}.bin", std::process::id()));
+        let start = Position::from_fen(DEFAULT_FEN).unwrap();
+        let mv = parse_move(&start, "b1c3").unwrap();
+        Book::from_moves(&[(start, mv, 1)]).save(&path).unwrap();
+
+        let book_file = format!("setoption name BookFile value {}", path.display());
+        let lines = session(&[&book_file, "setoption name OwnBook value true", "go"]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(lines.last().map(String::as_str), Some("bestmove b1c3"));
+    }
+
+    #[test]
+    fn test_go_infinite_book_move_waits_for_stop() {
+        let path =
+            std::env::temp_dir().join(format!("ruch-uci-infinite-book-{}.bin", std::process::id()));
+        let start = Position::from_fen(DEFAULT_FEN).unwrap();
+        let mv = parse_move(&start, "b1c3").unwrap();
+        Book::from_moves(&[(start, mv, 1)]).save(&path).unwrap();
+
+        let buffer = SharedBuffer::default();
+        let mut uci = Uci::new(buffer.clone());
+
+        uci.handle(&format!("setoption name BookFile value {}", path.display()));
+        uci.handle("setoption name OwnBook value true");
+        uci.handle("go infinite");
+        thread::sleep(Duration::from_millis(50));
+        assert!(buffer.lines().is_empty());
+
+        uci.handle("stop");
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(buffer.lines(), vec!["bestmove b1c3".to_string()]);
+    }
+
+    #[test]
+    fn test_go_searchmoves_skips_own_book() {
+        let path = std::env::temp_dir().join(format!(
+            "ruch-uci-searchmoves-book-{}.bin",
+            std::process::id()
+        ));
+        let start = Position::from_fen(DEFAULT_FEN).unwrap();
+        let mv = parse_move(&start, "b1c3").unwrap();
+        Book::from_moves(&[(start, mv, 1)]).save(&path).unwrap();
+
+        let book_file = format!("setoption name BookFile value {}", path.display());
+        let lines = session(&[
+            &book_file,
+            "setoption name OwnBook value true",
+            "go depth 1 searchmoves e2e4",
+        ]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(lines.last().map(String::as_str), Some("bestmove e2e4"));
+    }
+}
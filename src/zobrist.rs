@@ -0,0 +1,114 @@
+use crate::board::{board_flat, BoardFlat};
+use crate::piece::{Piece, PIECE_COUNT};
+use crate::position::CastlingRights;
+use crate::types::square::Square;
+
+// Fixed seed so keys, and with them any stored hashes, are the same in every build.
+const ZOBRIST_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+const CASTLING_RIGHTS_COUNT: usize = 16;
+
+pub struct ZobristKeys {
+    pieces: [BoardFlat<u64>; PIECE_COUNT],
+    black_to_move: u64,
+    castling_rights: [u64; CASTLING_RIGHTS_COUNT],
+    en_passant_files: [u64; 8],
+}
+
+const fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut state = ZOBRIST_SEED;
+    let mut pieces = [board_flat(0); PIECE_COUNT];
+    let mut castling_rights = [0; CASTLING_RIGHTS_COUNT];
+    let mut en_passant_files = [0; 8];
+
+    let mut piece = 0;
+    while piece < PIECE_COUNT {
+        let mut square = 0;
+        while square < pieces[piece].len() {
+            pieces[piece][square] = split_mix(&mut state);
+            square += 1;
+        }
+        piece += 1;
+    }
+
+    let black_to_move = split_mix(&mut state);
+
+    // No castling rights hash to zero, so positions without rights need no extra key.
+    let mut i = 1;
+    while i < CASTLING_RIGHTS_COUNT {
+        castling_rights[i] = split_mix(&mut state);
+        i += 1;
+    }
+
+    let mut file = 0;
+    while file < en_passant_files.len() {
+        en_passant_files[file] = split_mix(&mut state);
+        file += 1;
+    }
+
+    ZobristKeys {
+        pieces,
+        black_to_move,
+        castling_rights,
+        en_passant_files,
+    }
+}
+
+pub static ZOBRIST: ZobristKeys = generate_keys();
+
+impl ZobristKeys {
+    pub fn piece(&self, piece: Piece, square: Square) -> u64 {
+        self.pieces[piece.index()][square as usize]
+    }
+
+    pub fn black_to_move(&self) -> u64 {
+        self.black_to_move
+    }
+
+    pub fn castling_rights(&self, rights: CastlingRights) -> u64 {
+        self.castling_rights[rights.bits() as usize]
+    }
+
+    pub fn en_passant(&self, square: Option<Square>) -> u64 {
+        match square {
+            Some(square) => self.en_passant_files[square as usize & 7],
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::piece::PIECE_SET;
+    use std::collections::HashSet;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let mut keys = HashSet::new();
+
+        for piece in PIECE_SET {
+            for square in Square::iter() {
+                keys.insert(ZOBRIST.piece(piece, square));
+            }
+        }
+
+        keys.insert(ZOBRIST.black_to_move());
+        keys.extend(ZOBRIST.castling_rights[1..].iter().copied());
+        keys.extend(ZOBRIST.en_passant_files.iter().copied());
+
+        assert_eq!(keys.len(), PIECE_COUNT * 64 + 1 + 15 + 8);
+        assert!(!keys.contains(&0));
+        assert_eq!(ZOBRIST.castling_rights(CastlingRights::NONE), 0);
+    }
+}